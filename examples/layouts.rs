@@ -0,0 +1,69 @@
+//! Compares the proof size and prover time of the Fibonacci layouts.
+//!
+//! ```text
+//! cargo run --release --example layouts [n ...]
+//! ```
+
+use std::time::{Duration, Instant};
+
+use halo2_hope::circuit::{public_inputs, FibonacciCircuit};
+use halo2_hope::single_column::SingleColumnCircuit;
+use halo2_hope::two_column::TwoColumnCircuit;
+use halo2_proofs::circuit::Value;
+use halo2_proofs::pasta::{EqAffine, Fp};
+use halo2_proofs::plonk::{create_proof, keygen_pk, keygen_vk, verify_proof, Circuit, SingleVerifier};
+use halo2_proofs::poly::commitment::Params;
+use halo2_proofs::transcript::{Blake2bRead, Blake2bWrite, Challenge255};
+use rand_core::OsRng;
+
+struct Report {
+    k: u32,
+    proof_size: usize,
+    prover_time: Duration,
+}
+
+/// Generates keys for `circuit` and creates and verifies one proof of it.
+fn measure<C: Circuit<Fp>>(k: u32, circuit: C, public_inputs: &[Fp]) -> Report {
+    let params = Params::<EqAffine>::new(k);
+    let vk = keygen_vk(&params, &circuit.without_witnesses()).unwrap();
+    let pk = keygen_pk(&params, vk, &circuit.without_witnesses()).unwrap();
+
+    let start = Instant::now();
+    let mut transcript = Blake2bWrite::<_, _, Challenge255<_>>::init(vec![]);
+    create_proof(&params, &pk, &[circuit], &[&[public_inputs]], OsRng, &mut transcript).unwrap();
+    let proof = transcript.finalize();
+    let prover_time = start.elapsed();
+
+    let strategy = SingleVerifier::new(&params);
+    let mut transcript = Blake2bRead::<_, _, Challenge255<_>>::init(&proof[..]);
+    verify_proof(&params, pk.get_vk(), strategy, &[&[public_inputs]], &mut transcript).unwrap();
+
+    Report { k, proof_size: proof.len(), prover_time }
+}
+
+fn main() {
+    let ns: Vec<usize> = std::env::args().skip(1).map(|n| n.parse().expect("n is a number")).collect();
+    let ns = if ns.is_empty() { vec![100, 1000, 10000] } else { ns };
+
+    println!("| layout        |     n |  k | proof size | prover time |");
+    println!("|---------------|-------|----|------------|-------------|");
+    for n in ns {
+        let (seed_0, seed_1) = (Value::known(Fp::zero()), Value::known(Fp::one()));
+        let inputs = public_inputs(n, Fp::zero(), Fp::one());
+
+        let three_column = FibonacciCircuit::new(n, seed_0, seed_1);
+        let two_column = TwoColumnCircuit::new(n, seed_0, seed_1);
+        let single_column = SingleColumnCircuit::new(n, seed_0, seed_1);
+
+        for (name, report) in [
+            ("three-column", measure(three_column.k(), three_column, &inputs)),
+            ("two-column", measure(two_column.k(), two_column, &inputs)),
+            ("single-column", measure(single_column.k(), single_column, &inputs)),
+        ] {
+            println!(
+                "| {:13} | {:5} | {:2} | {:6} B   | {:8.1?}  |",
+                name, n, report.k, report.proof_size, report.prover_time
+            );
+        }
+    }
+}
@@ -0,0 +1,97 @@
+//! Compares laying the three-column chain out with one region per step
+//! against a single region holding the whole chain.
+//!
+//! ```text
+//! cargo run --release --example regions [n ...]
+//! ```
+
+use std::time::{Duration, Instant};
+
+use halo2_hope::circuit::{public_inputs, FibonacciCircuit};
+use halo2_hope::fibonacci::{FibonacciChip, FibonacciConfig};
+use halo2_proofs::circuit::{Layouter, SimpleFloorPlanner, Value};
+use halo2_proofs::pasta::{EqAffine, Fp};
+use halo2_proofs::plonk::{create_proof, keygen_pk, keygen_vk, Circuit, ConstraintSystem, Error};
+use halo2_proofs::poly::commitment::Params;
+use halo2_proofs::transcript::{Blake2bWrite, Challenge255};
+use rand_core::OsRng;
+
+/// The same chain as [`FibonacciCircuit`], assigned with
+/// [`FibonacciChip::assign_chain`].
+#[derive(Clone, Default)]
+struct ChainCircuit {
+    n: usize,
+}
+
+impl Circuit<Fp> for ChainCircuit {
+    type Config = FibonacciConfig;
+
+    type FloorPlanner = SimpleFloorPlanner;
+
+    fn without_witnesses(&self) -> Self {
+        self.clone()
+    }
+
+    fn configure(meta: &mut ConstraintSystem<Fp>) -> Self::Config {
+        FibonacciChip::configure(meta)
+    }
+
+    fn synthesize(&self, config: Self::Config, mut layouter: impl Layouter<Fp>) -> Result<(), Error> {
+        let chip = FibonacciChip::construct(config);
+
+        let (seed_0, seed_1) = (Value::known(Fp::zero()), Value::known(Fp::one()));
+        let (seed_0, seed_1, out) = chip.assign_chain(layouter.namespace(|| "chain"), self.n, seed_0, seed_1)?;
+        chip.expose_public(layouter.namespace(|| "out"), &out, 0)?;
+        chip.expose_public(layouter.namespace(|| "seed_0"), &seed_0, 1)?;
+        chip.expose_public(layouter.namespace(|| "seed_1"), &seed_1, 2)
+    }
+}
+
+struct Report {
+    keygen_time: Duration,
+    prover_time: Duration,
+}
+
+/// Times key generation and the creation of one proof of `circuit`.
+fn measure<C: Circuit<Fp>>(params: &Params<EqAffine>, circuit: C, public_inputs: &[Fp]) -> Report {
+    let start = Instant::now();
+    let vk = keygen_vk(params, &circuit.without_witnesses()).unwrap();
+    let pk = keygen_pk(params, vk, &circuit.without_witnesses()).unwrap();
+    let keygen_time = start.elapsed();
+
+    let start = Instant::now();
+    let mut transcript = Blake2bWrite::<_, _, Challenge255<_>>::init(vec![]);
+    create_proof(params, &pk, &[circuit], &[&[public_inputs]], OsRng, &mut transcript).unwrap();
+    let prover_time = start.elapsed();
+
+    Report { keygen_time, prover_time }
+}
+
+fn main() {
+    let ns: Vec<usize> = std::env::args().skip(1).map(|n| n.parse().expect("n is a number")).collect();
+    let ns = if ns.is_empty() { vec![1000, 10000, 50000] } else { ns };
+
+    println!("| regions  |     n |  k | keygen time | prover time |");
+    println!("|----------|-------|----|-------------|-------------|");
+    for n in ns {
+        let inputs = public_inputs(n, Fp::zero(), Fp::one());
+
+        let per_step = FibonacciCircuit::new(n, Value::known(Fp::zero()), Value::known(Fp::one()));
+        let k = per_step.k();
+        let params = Params::<EqAffine>::new(k);
+
+        for (name, report) in [
+            ("per step", measure(&params, per_step, &inputs)),
+            ("single", measure(&params, ChainCircuit { n }, &inputs)),
+        ] {
+            println!(
+                "| {:8} | {:5} | {:2} | {:>11} | {:>11} |",
+                name,
+                n,
+                k,
+                format!("{:.1?}", report.keygen_time),
+                format!("{:.1?}", report.prover_time)
+            );
+        }
+    }
+}
@@ -0,0 +1,326 @@
+/*
+
+    m independent chains side by side, one lane of three columns each:
+
+    |        lane 0        |        lane 1        | ... | q_fib | instance
+    ---------------------------------------------------------------------
+    |  a_0  |  a_1  |  a_2  |  b_0  |  b_1  |  b_2  | ... |   1   |   a_n
+    |  a_1  |  a_2  |  a_3  |  b_1  |  b_2  |  b_3  | ... |   1   |   a_0
+    |  ...  |  ...  |  ...  |  ...  |  ...  |  ...  | ... |   1   |   a_1
+    |       |       |       |       |       |       |     |       |   b_n
+    |       |       |       |       |       |       |     |       |   ...
+
+    q_fib * (elem_1 + elem_2 - elem_3) = 0    for every lane
+
+    All lanes advance in lockstep under the one selector. Lane i exposes its
+    last elem_3 in row 3i of the instance column and its seeds in rows 3i + 1
+    and 3i + 2, as `circuit::LayoutCircuit` does for a single chain.
+
+*/
+
+use std::marker::PhantomData;
+
+use halo2_proofs::arithmetic::FieldExt;
+use halo2_proofs::circuit::{AssignedCell, Chip, Layouter, SimpleFloorPlanner, Value};
+use halo2_proofs::plonk::*;
+use halo2_proofs::poly::Rotation;
+
+use crate::circuit::min_k;
+use crate::fibonacci::{ChainCells, FibonacciCells};
+
+/// The three advice columns of one chain.
+#[derive(Clone, Debug, Copy)]
+pub struct Lane {
+    pub elem_1: Column<Advice>,
+    pub elem_2: Column<Advice>,
+    pub elem_3: Column<Advice>,
+}
+
+/// Columns and selector used by the `batched fibonacci` gate.
+#[derive(Clone, Debug)]
+pub struct BatchConfig {
+    pub lanes: Vec<Lane>,
+    pub q_fib: Selector,
+    pub instance: Column<Instance>,
+}
+
+/// A chip proving one Fibonacci step in each of several chains per row.
+#[derive(Clone, Debug)]
+pub struct BatchChip<F: FieldExt> {
+    config: BatchConfig,
+    _marker: PhantomData<F>,
+}
+
+impl<F: FieldExt> Chip<F> for BatchChip<F> {
+    type Config = BatchConfig;
+    type Loaded = ();
+
+    fn config(&self) -> &Self::Config {
+        &self.config
+    }
+
+    fn loaded(&self) -> &Self::Loaded {
+        &()
+    }
+}
+
+impl<F: FieldExt> BatchChip<F> {
+    /// Constructs a chip from a config returned by [`BatchChip::configure`].
+    pub fn construct(config: BatchConfig) -> Self {
+        Self {
+            config,
+            _marker: PhantomData,
+        }
+    }
+
+    /// Allocates three equality-enabled advice columns per lane, a selector
+    /// and an instance column, and creates the `batched fibonacci` gate over
+    /// them.
+    pub fn configure(cs: &mut ConstraintSystem<F>, lanes: usize) -> BatchConfig {
+        assert!(lanes >= 1, "the batch has at least one chain");
+
+        let mut advice = || {
+            let column = cs.advice_column();
+            cs.enable_equality(column);
+            column
+        };
+        let lanes: Vec<_> = (0..lanes)
+            .map(|_| Lane { elem_1: advice(), elem_2: advice(), elem_3: advice() })
+            .collect();
+        let q_fib = cs.selector();
+        let instance = cs.instance_column();
+        cs.enable_equality(instance);
+
+        cs.create_gate("batched fibonacci", |virtual_cells| {
+            let q_fib = virtual_cells.query_selector(q_fib);
+            let constraints: Vec<_> = lanes
+                .iter()
+                .map(|lane| {
+                    let elem_1 = virtual_cells.query_advice(lane.elem_1, Rotation::cur());
+                    let elem_2 = virtual_cells.query_advice(lane.elem_2, Rotation::cur());
+                    let elem_3 = virtual_cells.query_advice(lane.elem_3, Rotation::cur());
+
+                    //     q_fib * (elem_1 + elem_2 - elem_3) = 0
+                    elem_1 + elem_2 - elem_3
+                })
+                .collect();
+
+            Constraints::with_selector(q_fib, constraints)
+        });
+
+        BatchConfig { lanes, q_fib, instance }
+    }
+
+    /// Assigns the first row of every chain from its two seeds, returning the
+    /// `(elem_2, elem_3)` cells of each lane.
+    pub fn init(
+        &self,
+        layouter: impl Layouter<F>,
+        seeds: &[(Value<F>, Value<F>)],
+    ) -> Result<Vec<FibonacciCells<F>>, Error> {
+        let lanes = self.init_with_seeds(layouter, seeds)?;
+        Ok(lanes.into_iter().map(|(_, elem_2, elem_3)| (elem_2, elem_3)).collect())
+    }
+
+    /// Like [`BatchChip::init`], but returns all three cells of each lane's
+    /// first row, so that the seeds can be constrained by the caller.
+    pub fn init_with_seeds(
+        &self,
+        mut layouter: impl Layouter<F>,
+        seeds: &[(Value<F>, Value<F>)],
+    ) -> Result<Vec<ChainCells<F>>, Error> {
+        let config = self.config();
+        assert_eq!(seeds.len(), config.lanes.len(), "expected one pair of seeds per lane");
+
+        layouter.assign_region(|| "init batched Fibonacci", |mut region| {
+            let offset = 0;
+
+            // Enable q_fib
+            config.q_fib.enable(&mut region, offset)?;
+
+            seeds
+                .iter()
+                .zip(config.lanes.iter())
+                .map(|((elem_1, elem_2), lane)| {
+                    // Assign the seeds
+                    let elem_1 = region.assign_advice(|| "elem_1", lane.elem_1, offset, || *elem_1)?;
+                    let elem_2 = region.assign_advice(|| "elem_2", lane.elem_2, offset, || *elem_2)?;
+
+                    let elem_3 = elem_1.value().copied() + elem_2.value();
+                    // Assign elem_3
+                    let elem_3 = region.assign_advice(|| "elem_3", lane.elem_3, offset, || elem_3)?;
+
+                    Ok((elem_1, elem_2, elem_3))
+                })
+                .collect()
+        })
+    }
+
+    /// Assigns one more step of every chain, copying the cells returned by the
+    /// previous step into the new row.
+    pub fn assign(
+        &self,
+        mut layouter: impl Layouter<F>,
+        prev: &[FibonacciCells<F>],
+    ) -> Result<Vec<FibonacciCells<F>>, Error> {
+        let config = self.config();
+        assert_eq!(prev.len(), config.lanes.len(), "expected one pair of cells per lane");
+
+        layouter.assign_region(|| "steady-state batched Fibonacci", |mut region| {
+            let offset = 0;
+
+            // Enable q_fib
+            config.q_fib.enable(&mut region, offset)?;
+
+            prev.iter()
+                .zip(config.lanes.iter())
+                .map(|((elem_2, elem_3), lane)| {
+                    // Copy elem_1 (which is the previous elem_2)
+                    let elem_1 = elem_2.copy_advice(|| "copy elem_2 into current elem_1", &mut region, lane.elem_1, offset)?;
+
+                    // Copy elem_2 (which is the previous elem_3)
+                    let elem_2 = elem_3.copy_advice(|| "copy elem_3 into current elem_2", &mut region, lane.elem_2, offset)?;
+
+                    let elem_3 = elem_1.value().copied() + elem_2.value();
+                    // Assign elem_3
+                    let elem_3 = region.assign_advice(|| "elem_3", lane.elem_3, offset, || elem_3)?;
+
+                    Ok((elem_2, elem_3))
+                })
+                .collect()
+        })
+    }
+
+    /// Constrains `cell` to equal the given `row` of the instance column.
+    pub fn expose_public(
+        &self,
+        mut layouter: impl Layouter<F>,
+        cell: &AssignedCell<F, F>,
+        row: usize,
+    ) -> Result<(), Error> {
+        layouter.constrain_instance(cell.cell(), self.config().instance, row)
+    }
+}
+
+/// A circuit proving the n-th term of `M` sequences at once, exposing the
+/// term of sequence i in row 3i of the instance column and its seeds in rows
+/// 3i + 1 and 3i + 2.
+#[derive(Clone, Debug)]
+pub struct BatchCircuit<F: FieldExt, const M: usize> {
+    n: usize,
+    seeds: [(Value<F>, Value<F>); M],
+}
+
+impl<F: FieldExt, const M: usize> Default for BatchCircuit<F, M> {
+    fn default() -> Self {
+        Self {
+            n: 0,
+            seeds: [(Value::unknown(), Value::unknown()); M],
+        }
+    }
+}
+
+impl<F: FieldExt, const M: usize> BatchCircuit<F, M> {
+    /// Creates a circuit for the n-th term of each sequence seeded with the
+    /// given pairs, which must have `n >= 2`.
+    pub fn new(n: usize, seeds: [(Value<F>, Value<F>); M]) -> Self {
+        assert!(n >= 2, "the circuit has at least one Fibonacci step");
+        Self { n, seeds }
+    }
+
+    /// The smallest `k` for which the circuit fits.
+    pub fn k(&self) -> u32 {
+        let mut cs = ConstraintSystem::default();
+        Self::configure(&mut cs);
+        // The instance column holds the output and the two seeds of each lane.
+        min_k(std::cmp::max(self.n - 1, 3 * M), &cs)
+    }
+}
+
+impl<F: FieldExt, const M: usize> Circuit<F> for BatchCircuit<F, M> {
+    type Config = BatchConfig;
+
+    type FloorPlanner = SimpleFloorPlanner;
+
+    fn without_witnesses(&self) -> Self {
+        Self { n: self.n, ..Self::default() }
+    }
+
+    fn configure(meta: &mut ConstraintSystem<F>) -> Self::Config {
+        BatchChip::configure(meta, M)
+    }
+
+    fn synthesize(&self, config: Self::Config, mut layouter: impl Layouter<F>) -> Result<(), Error> {
+        let chip = BatchChip::construct(config);
+
+        let lanes = chip.init_with_seeds(layouter.namespace(|| "init"), &self.seeds)?;
+        let (seeds, mut cells): (Vec<_>, Vec<_>) =
+            lanes.into_iter().map(|(elem_1, elem_2, elem_3)| ((elem_1, elem_2.clone()), (elem_2, elem_3))).unzip();
+        for i in 3..=self.n {
+            cells = chip.assign(layouter.namespace(|| format!("x_{}", i)), &cells)?;
+        }
+
+        for (lane, ((elem_1, elem_2), (_, elem_3))) in seeds.iter().zip(cells.iter()).enumerate() {
+            chip.expose_public(layouter.namespace(|| format!("out {}", lane)), elem_3, 3 * lane)?;
+            chip.expose_public(layouter.namespace(|| format!("elem_1 {}", lane)), elem_1, 3 * lane + 1)?;
+            chip.expose_public(layouter.namespace(|| format!("elem_2 {}", lane)), elem_2, 3 * lane + 2)?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use halo2_proofs::{dev::MockProver, pasta::Fp};
+
+    use super::*;
+    use crate::circuit::public_inputs;
+
+    fn seeds<const M: usize>() -> [(Fp, Fp); M] {
+        let mut seeds = [(Fp::zero(), Fp::zero()); M];
+        for (i, seed) in seeds.iter_mut().enumerate() {
+            *seed = (Fp::from(i as u64), Fp::from(2 * i as u64 + 1));
+        }
+        seeds
+    }
+
+    #[test]
+    fn test_batch() {
+        let seeds = seeds::<24>();
+        let circuit = BatchCircuit::new(30, seeds.map(|(a, b)| (Value::known(a), Value::known(b))));
+        let inputs: Vec<_> = seeds.iter().flat_map(|(a, b)| public_inputs(30, *a, *b)).collect();
+
+        let prover = MockProver::run(circuit.k(), &circuit, vec![inputs]).unwrap();
+        prover.assert_satisfied();
+    }
+
+    #[test]
+    fn test_wrong_lane() {
+        let seeds = seeds::<3>();
+        let circuit = BatchCircuit::new(10, seeds.map(|(a, b)| (Value::known(a), Value::known(b))));
+        let mut inputs: Vec<_> = seeds.iter().flat_map(|(a, b)| public_inputs(10, *a, *b)).collect();
+
+        // Swapping two lanes' outputs is caught.
+        inputs.swap(3, 6);
+        let prover = MockProver::run(circuit.k(), &circuit, vec![inputs]).unwrap();
+        assert!(prover.verify().is_err());
+    }
+
+    #[test]
+    fn test_public_seeds() {
+        // x_10 = 34 * x_0 + 55 * x_1, so (355, 5) also reaches 12345.
+        let seeds = [(Fp::zero(), Fp::one()), (Fp::from(355), Fp::from(5))];
+        let circuit = BatchCircuit::new(10, seeds.map(|(a, b)| (Value::known(a), Value::known(b))));
+        let mut inputs: Vec<_> = seeds.iter().flat_map(|(a, b)| public_inputs(10, *a, *b)).collect();
+        assert_eq!(inputs[3], Fp::from(12345));
+
+        let prover = MockProver::run(circuit.k(), &circuit, vec![inputs.clone()]).unwrap();
+        prover.assert_satisfied();
+
+        // Lane 1's output cannot be passed off as coming from the seeds 0 and 1.
+        inputs[4] = Fp::zero();
+        inputs[5] = Fp::one();
+        let prover = MockProver::run(circuit.k(), &circuit, vec![inputs]).unwrap();
+        assert!(prover.verify().is_err());
+    }
+}
@@ -0,0 +1,372 @@
+/*
+
+    Fibonacci over integers of L 64-bit limbs, little-endian, so terms can
+    exceed the field modulus. Each step adds two terms limb by limb in one
+    region of L + 1 rows, e.g. for L = 2:
+
+    |  a  |  b  |  c  | carry | q_add
+    ---------------------------------
+    | a_0 | b_0 | c_0 |   0   |   1
+    | a_1 | b_1 | c_1 |  k_1  |   1
+    |     |     |     |   0   |   0
+
+    q_add * carry_next * (1 - carry_next) = 0
+    q_add * (a + b + carry - c - carry_next * 2^64) = 0
+
+    carry is the carry into each limb, fixed to 0 into the first limb and
+    constrained to 0 out of the last, so the sum does not overflow L limbs.
+    Every c is range-checked below 2^64, and the next step copies the b and c
+    limbs into its a and b columns.
+
+*/
+
+use std::marker::PhantomData;
+
+use halo2_proofs::arithmetic::FieldExt;
+use halo2_proofs::circuit::{AssignedCell, Chip, Layouter, Region, SimpleFloorPlanner};
+use halo2_proofs::plonk::*;
+use halo2_proofs::poly::Rotation;
+
+use crate::circuit::min_k;
+use crate::range_check::{low_u64, RangeCheckChip, RangeCheckConfig, K};
+
+/// The limbs of the last two terms of a chain, `(elem_2, elem_3)`.
+pub type BigIntCells<F> = (Vec<AssignedCell<F, F>>, Vec<AssignedCell<F, F>>);
+
+/// Computes the limbs of F(n), or `None` if it does not fit in `num_limbs`
+/// limbs.
+pub fn nth_term(n: usize, num_limbs: usize) -> Option<Vec<u64>> {
+    let mut a = vec![0u64; num_limbs];
+    let mut b = vec![0u64; num_limbs];
+    *b.first_mut()? = 1;
+
+    if n == 0 {
+        return Some(a);
+    }
+    for _ in 1..n {
+        let mut carry = false;
+        let c: Vec<_> = a
+            .iter()
+            .zip(b.iter())
+            .map(|(a, b)| {
+                let (sum, carry_1) = a.overflowing_add(*b);
+                let (sum, carry_2) = sum.overflowing_add(carry as u64);
+                carry = carry_1 || carry_2;
+                sum
+            })
+            .collect();
+        if carry {
+            return None;
+        }
+        (a, b) = (b, c);
+    }
+    Some(b)
+}
+
+/// Columns and selector used by the `big integer addition` gate.
+#[derive(Clone, Debug, Copy)]
+pub struct BigIntConfig {
+    pub a: Column<Advice>,
+    pub b: Column<Advice>,
+    pub c: Column<Advice>,
+    pub carry: Column<Advice>,
+    pub constants: Column<Fixed>,
+    pub q_add: Selector,
+    pub instance: Column<Instance>,
+    pub range_check: RangeCheckConfig,
+}
+
+/// A chip proving Fibonacci steps over `num_limbs`-limb integers.
+#[derive(Clone, Debug)]
+pub struct BigIntChip<F: FieldExt> {
+    config: BigIntConfig,
+    num_limbs: usize,
+    _marker: PhantomData<F>,
+}
+
+impl<F: FieldExt> Chip<F> for BigIntChip<F> {
+    type Config = BigIntConfig;
+    type Loaded = ();
+
+    fn config(&self) -> &Self::Config {
+        &self.config
+    }
+
+    fn loaded(&self) -> &Self::Loaded {
+        &()
+    }
+}
+
+impl<F: FieldExt> BigIntChip<F> {
+    /// Constructs a chip from a config returned by [`BigIntChip::configure`],
+    /// for integers of `num_limbs` limbs.
+    pub fn construct(config: BigIntConfig, num_limbs: usize) -> Self {
+        assert!(num_limbs >= 1, "an integer has at least one limb");
+        Self {
+            config,
+            num_limbs,
+            _marker: PhantomData,
+        }
+    }
+
+    /// Allocates four equality-enabled advice columns, a constants column, a
+    /// selector and an instance column, and creates the
+    /// `big integer addition` gate over them. Also configures a
+    /// [`RangeCheckChip`].
+    pub fn configure(cs: &mut ConstraintSystem<F>) -> BigIntConfig {
+        let a = cs.advice_column();
+        cs.enable_equality(a);
+        let b = cs.advice_column();
+        cs.enable_equality(b);
+        let c = cs.advice_column();
+        cs.enable_equality(c);
+        let carry = cs.advice_column();
+        cs.enable_equality(carry);
+        let constants = cs.fixed_column();
+        cs.enable_constant(constants);
+        let q_add = cs.selector();
+        let instance = cs.instance_column();
+        cs.enable_equality(instance);
+        let range_check = RangeCheckChip::configure(cs);
+
+        cs.create_gate("big integer addition", |virtual_cells| {
+            let q_add = virtual_cells.query_selector(q_add);
+            let a = virtual_cells.query_advice(a, Rotation::cur());
+            let b = virtual_cells.query_advice(b, Rotation::cur());
+            let c = virtual_cells.query_advice(c, Rotation::cur());
+            let carry_cur = virtual_cells.query_advice(carry, Rotation::cur());
+            let carry_next = virtual_cells.query_advice(carry, Rotation::next());
+
+            let one = Expression::Constant(F::one());
+            let two_pow_64 = Expression::Constant(F::from_u128(1 << 64));
+
+            Constraints::with_selector(
+                q_add,
+                [
+                    ("carry_next is boolean", carry_next.clone() * (one - carry_next.clone())),
+                    ("limb sum", a + b + carry_cur - c - carry_next * two_pow_64),
+                ],
+            )
+        });
+
+        BigIntConfig { a, b, c, carry, constants, q_add, instance, range_check }
+    }
+
+    /// Loads the range check table. Must be called once per circuit.
+    pub fn load_table(&self, layouter: impl Layouter<F>) -> Result<(), Error> {
+        RangeCheckChip::construct(self.config().range_check).load_table(layouter)
+    }
+
+    /// Adds two integers limb by limb, where `operands` assigns limb `i` of
+    /// both into the `a` and `b` columns at row `i`. Returns the limbs of the
+    /// second operand and of the range-checked sum.
+    ///
+    /// Returns [`Error::Synthesis`] if the sum is known and overflows.
+    fn add(
+        &self,
+        mut layouter: impl Layouter<F>,
+        name: &str,
+        operands: impl Fn(&mut Region<'_, F>, usize) -> Result<(AssignedCell<F, F>, AssignedCell<F, F>), Error>,
+    ) -> Result<BigIntCells<F>, Error> {
+        let config = self.config();
+        let range_check = RangeCheckChip::construct(config.range_check);
+
+        let (b, c) = layouter.assign_region(|| name, |mut region| {
+            let (mut b, mut c) = (vec![], vec![]);
+
+            // No carry into the first limb
+            let mut carry = region.assign_advice_from_constant(|| "carry_0", config.carry, 0, F::zero())?;
+
+            for offset in 0..self.num_limbs {
+                // Enable q_add
+                config.q_add.enable(&mut region, offset)?;
+
+                let (a_i, b_i) = operands(&mut region, offset)?;
+
+                let sum = a_i.value().zip(b_i.value()).zip(carry.value()).map(|((a, b), carry)| {
+                    low_u64(a) as u128 + low_u64(b) as u128 + low_u64(carry) as u128
+                });
+                // Assign c and the carry out of this limb
+                let c_i = region.assign_advice(|| format!("c_{}", offset), config.c, offset, || sum.map(|sum| F::from(sum as u64)))?;
+                let carry_next = sum.map(|sum| F::from((sum >> 64) as u64));
+                carry = region.assign_advice(|| format!("carry_{}", offset + 1), config.carry, offset + 1, || carry_next)?;
+
+                b.push(b_i);
+                c.push(c_i);
+            }
+
+            // The sum fits in num_limbs limbs
+            carry.value().error_if_known_and(|carry| **carry != F::zero())?;
+            region.constrain_constant(carry.cell(), F::zero())?;
+
+            Ok((b, c))
+        })?;
+
+        for (i, c_i) in c.iter().enumerate() {
+            range_check.copy_check(layouter.namespace(|| format!("range check c_{}", i)), c_i, 64)?;
+        }
+        Ok((b, c))
+    }
+
+    /// Assigns the first step of the chain from the constant seeds 0 and 1,
+    /// returning the limbs of `elem_2` and `elem_3`.
+    pub fn init_standard(&self, layouter: impl Layouter<F>) -> Result<BigIntCells<F>, Error> {
+        let config = self.config();
+
+        self.add(layouter, "init big integer Fibonacci", |region, offset| {
+            let a = region.assign_advice_from_constant(|| format!("a_{}", offset), config.a, offset, F::zero())?;
+            let b = region.assign_advice_from_constant(|| format!("b_{}", offset), config.b, offset, F::from((offset == 0) as u64))?;
+            Ok((a, b))
+        })
+    }
+
+    /// Assigns one more step of the chain, copying the limbs of the previous
+    /// `elem_2` and `elem_3` into the new region.
+    pub fn assign(&self, layouter: impl Layouter<F>, prev: &BigIntCells<F>) -> Result<BigIntCells<F>, Error> {
+        let config = self.config();
+        let (elem_2, elem_3) = prev;
+        assert_eq!(elem_2.len(), self.num_limbs, "expected one cell per limb");
+
+        self.add(layouter, "steady-state big integer Fibonacci", |region, offset| {
+            let a = elem_2[offset].copy_advice(|| format!("copy elem_2 limb {} into a", offset), region, config.a, offset)?;
+            let b = elem_3[offset].copy_advice(|| format!("copy elem_3 limb {} into b", offset), region, config.b, offset)?;
+            Ok((a, b))
+        })
+    }
+
+    /// Constrains the limbs of an integer to equal consecutive rows of the
+    /// instance column, starting at `row`.
+    pub fn expose_public(
+        &self,
+        mut layouter: impl Layouter<F>,
+        limbs: &[AssignedCell<F, F>],
+        row: usize,
+    ) -> Result<(), Error> {
+        for (i, limb) in limbs.iter().enumerate() {
+            layouter.constrain_instance(limb.cell(), self.config().instance, row + i)?;
+        }
+        Ok(())
+    }
+}
+
+/// A circuit proving F(n) as an integer of `num_limbs` limbs, exposing its
+/// limbs in rows 0 to `num_limbs - 1` of the instance column.
+///
+/// Synthesis fails if F(n) does not fit in `num_limbs` limbs.
+#[derive(Clone, Debug)]
+pub struct BigIntCircuit<F: FieldExt> {
+    n: usize,
+    num_limbs: usize,
+    _marker: PhantomData<F>,
+}
+
+impl<F: FieldExt> BigIntCircuit<F> {
+    /// Creates a circuit for F(n) in `num_limbs` limbs, which must have
+    /// `n >= 2`.
+    pub fn new(n: usize, num_limbs: usize) -> Self {
+        assert!(n >= 2, "the circuit has at least one Fibonacci step");
+        Self {
+            n,
+            num_limbs,
+            _marker: PhantomData,
+        }
+    }
+
+    /// The smallest `k` for which the circuit fits.
+    pub fn k(&self) -> u32 {
+        let mut cs = ConstraintSystem::default();
+        Self::configure(&mut cs);
+        // Each step takes num_limbs + 1 rows of addition and 64 / K + 1 rows
+        // of range check per limb.
+        let rows = (self.n - 1) * (self.num_limbs + 1 + self.num_limbs * (64 / K + 1));
+        min_k(std::cmp::max(rows, 1 << K), &cs)
+    }
+}
+
+impl<F: FieldExt> Circuit<F> for BigIntCircuit<F> {
+    type Config = BigIntConfig;
+
+    type FloorPlanner = SimpleFloorPlanner;
+
+    fn without_witnesses(&self) -> Self {
+        self.clone()
+    }
+
+    fn configure(meta: &mut ConstraintSystem<F>) -> Self::Config {
+        BigIntChip::configure(meta)
+    }
+
+    fn synthesize(&self, config: Self::Config, mut layouter: impl Layouter<F>) -> Result<(), Error> {
+        let chip = BigIntChip::construct(config, self.num_limbs);
+        chip.load_table(layouter.namespace(|| "table"))?;
+
+        let mut cells = chip.init_standard(layouter.namespace(|| "init"))?;
+        for i in 3..=self.n {
+            cells = chip.assign(layouter.namespace(|| format!("x_{}", i)), &cells)?;
+        }
+
+        chip.expose_public(layouter.namespace(|| "out"), &cells.1, 0)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use halo2_proofs::pasta::group::ff::PrimeField;
+    use halo2_proofs::{dev::MockProver, pasta::Fp};
+
+    use super::*;
+
+    fn to_field(limbs: &[u64]) -> Fp {
+        let two_pow_64 = Fp::from_u128(1 << 64);
+        limbs.iter().rev().fold(Fp::zero(), |acc, limb| acc * two_pow_64 + Fp::from(*limb))
+    }
+
+    #[test]
+    fn test_nth_term() {
+        assert_eq!(nth_term(93, 1), Some(vec![12200160415121876738]));
+        assert_eq!(nth_term(94, 1), None);
+        assert_eq!(nth_term(94, 2), Some(vec![1293530146158671551, 1]));
+        assert_eq!(nth_term(0, 1), Some(vec![0]));
+        assert_eq!(nth_term(1, 1), Some(vec![1]));
+
+        for n in [10, 200, 400] {
+            let limbs = nth_term(n, 5).unwrap();
+            assert_eq!(to_field(&limbs), crate::circuit::nth_term(n, Fp::zero(), Fp::one()));
+        }
+    }
+
+    #[test]
+    fn test_bigint() {
+        // F(380) is about 2^262, beyond the Pasta modulus.
+        for (n, num_limbs) in [(10, 1), (94, 2), (380, 5)] {
+            let circuit = BigIntCircuit::new(n, num_limbs);
+            let output: Vec<_> = nth_term(n, num_limbs).unwrap().into_iter().map(Fp::from).collect();
+
+            let prover = MockProver::run(circuit.k(), &circuit, vec![output]).unwrap();
+            prover.assert_satisfied();
+        }
+    }
+
+    #[test]
+    fn test_residue_rejected() {
+        let circuit = BigIntCircuit::new(380, 5);
+
+        // The limbs of F(380) mod p, as proven by the field circuit.
+        let residue = crate::circuit::nth_term(380, Fp::zero(), Fp::one());
+        let mut output: Vec<_> = residue
+            .to_repr()
+            .chunks(8)
+            .map(|chunk| Fp::from(u64::from_le_bytes(chunk.try_into().unwrap())))
+            .collect();
+        output.push(Fp::zero());
+
+        let prover = MockProver::run(circuit.k(), &circuit, vec![output]).unwrap();
+        assert!(prover.verify().is_err());
+    }
+
+    #[test]
+    fn test_overflow() {
+        let circuit = BigIntCircuit::<Fp>::new(94, 1);
+        assert!(MockProver::run(circuit.k(), &circuit, vec![vec![Fp::zero()]]).is_err());
+    }
+}
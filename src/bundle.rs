@@ -0,0 +1,414 @@
+//! A versioned on-disk container for Fibonacci proofs.
+//!
+//! All integers are little-endian and field elements are their canonical
+//! 32-byte representation:
+//!
+//! ```text
+//! | field           | encoding                              |
+//! |-----------------|---------------------------------------|
+//! | magic           | b"H2HP"                               |
+//! | version         | u16                                   |
+//! | circuit id      | u8 length, then that many UTF-8 bytes |
+//! | k               | u32                                   |
+//! | n               | u64                                   |
+//! | public inputs   | u32 count, then 32 bytes each         |
+//! | proof           | u32 length, then the proof bytes      |
+//! | vk fingerprint  | 32 bytes                              |
+//! ```
+
+use std::fmt;
+use std::io::{self, Read, Write};
+
+use halo2_proofs::pasta::group::ff::PrimeField;
+use halo2_proofs::pasta::{EqAffine, Fp};
+use halo2_proofs::plonk::VerifyingKey;
+use halo2_proofs::poly::commitment::Params;
+use halo2_proofs::transcript::{Blake2bWrite, Challenge255, EncodedChallenge, Transcript};
+
+use crate::proof::{params_k, Proof};
+
+/// The magic bytes every bundle starts with.
+pub const MAGIC: [u8; 4] = *b"H2HP";
+
+/// The current version of the bundle format.
+pub const VERSION: u16 = 1;
+
+/// The identifier of [`crate::circuit::FibonacciCircuit`].
+pub const FIBONACCI_CIRCUIT_ID: &str = "fibonacci";
+
+/// The number of public inputs of [`crate::circuit::FibonacciCircuit`]: the
+/// output and the two seeds.
+pub const FIBONACCI_PUBLIC_INPUTS: usize = 3;
+
+/// The largest proof a bundle may contain, to bound allocations when reading
+/// untrusted input.
+pub const MAX_PROOF_LEN: usize = 1 << 20;
+
+/// The largest `k` a bundle may claim, so that the verifier never reads or
+/// generates parameters larger than this.
+pub const MAX_K: u32 = 24;
+
+/// The largest number of public inputs a bundle may contain.
+pub const MAX_PUBLIC_INPUTS: usize = 1 << 10;
+
+/// Errors when reading or validating a [`ProofBundle`].
+#[derive(Debug)]
+pub enum BundleError {
+    /// The underlying reader or writer failed.
+    Io(io::Error),
+    /// The input does not start with [`MAGIC`].
+    BadMagic,
+    /// The bundle was written with a format version this crate cannot read.
+    UnsupportedVersion(u16),
+    /// The bundle is for a circuit other than the expected one.
+    UnknownCircuit(String),
+    /// A length field exceeds the format's limits.
+    TooLarge { field: &'static str, len: usize },
+    /// The public input at this index is not a canonical field element.
+    InvalidPublicInput(usize),
+    /// The bundle was proven with different parameters.
+    KMismatch { expected: u32, found: u32 },
+    /// The bundle has the wrong number of public inputs for its circuit.
+    PublicInputCount { expected: usize, found: usize },
+    /// The bundle was proven against a different verifying key.
+    FingerprintMismatch,
+}
+
+impl fmt::Display for BundleError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            BundleError::Io(e) => write!(f, "I/O error: {}", e),
+            BundleError::BadMagic => write!(f, "not a proof bundle"),
+            BundleError::UnsupportedVersion(version) => {
+                write!(f, "unsupported bundle version {} (expected {})", version, VERSION)
+            }
+            BundleError::UnknownCircuit(id) => write!(f, "unknown circuit `{}`", id),
+            BundleError::TooLarge { field, len } => write!(f, "{} too large ({})", field, len),
+            BundleError::InvalidPublicInput(i) => write!(f, "public input {} is not a field element", i),
+            BundleError::KMismatch { expected, found } => {
+                write!(f, "bundle is for k = {}, but the parameters have k = {}", found, expected)
+            }
+            BundleError::PublicInputCount { expected, found } => {
+                write!(f, "expected {} public inputs, found {}", expected, found)
+            }
+            BundleError::FingerprintMismatch => write!(f, "bundle is for a different verifying key"),
+        }
+    }
+}
+
+impl std::error::Error for BundleError {}
+
+impl From<io::Error> for BundleError {
+    fn from(e: io::Error) -> Self {
+        BundleError::Io(e)
+    }
+}
+
+/// Returns a 32-byte fingerprint identifying `vk`.
+///
+/// This is the challenge squeezed from a Blake2b transcript after hashing in
+/// the verifying key, so it commits to the constraint system, the fixed
+/// columns and the permutation, i.e. to both the gates and `n`.
+pub fn vk_fingerprint(vk: &VerifyingKey<EqAffine>) -> [u8; 32] {
+    let mut transcript = Blake2bWrite::<_, _, Challenge255<_>>::init(vec![]);
+    vk.hash_into(&mut transcript)
+        .expect("writing to a Vec cannot fail");
+    let challenge: Challenge255<EqAffine> = transcript.squeeze_challenge();
+    challenge.get_scalar().to_repr()
+}
+
+/// A proof together with everything needed to check it belongs to the
+/// verifier's circuit.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ProofBundle {
+    pub circuit_id: String,
+    pub k: u32,
+    pub n: u64,
+    pub public_inputs: Vec<Fp>,
+    pub proof: Vec<u8>,
+    pub vk_fingerprint: [u8; 32],
+}
+
+impl ProofBundle {
+    /// Bundles a proof created with `params` and the proving key whose
+    /// verifying key is `vk`.
+    pub fn new(params: &Params<EqAffine>, vk: &VerifyingKey<EqAffine>, proof: &Proof) -> Self {
+        ProofBundle {
+            circuit_id: FIBONACCI_CIRCUIT_ID.to_string(),
+            k: params_k(params),
+            n: proof.n as u64,
+            public_inputs: proof.public_inputs(),
+            proof: proof.bytes.clone(),
+            vk_fingerprint: vk_fingerprint(vk),
+        }
+    }
+
+    /// The proof contained in this bundle, or an error if the bundle does not
+    /// hold the output and seeds of [`crate::circuit::FibonacciCircuit`].
+    pub fn to_proof(&self) -> Result<Proof, BundleError> {
+        match self.public_inputs[..] {
+            [output, elem_1, elem_2] => Ok(Proof {
+                n: self.n as usize,
+                seeds: (elem_1, elem_2),
+                output,
+                bytes: self.proof.clone(),
+            }),
+            _ => Err(BundleError::PublicInputCount {
+                expected: FIBONACCI_PUBLIC_INPUTS,
+                found: self.public_inputs.len(),
+            }),
+        }
+    }
+
+    /// Checks that this bundle belongs to the circuit with `params` and `vk`.
+    pub fn validate(&self, params: &Params<EqAffine>, vk: &VerifyingKey<EqAffine>) -> Result<(), BundleError> {
+        if self.circuit_id != FIBONACCI_CIRCUIT_ID {
+            return Err(BundleError::UnknownCircuit(self.circuit_id.clone()));
+        }
+
+        let expected = params_k(params);
+        if self.k != expected {
+            return Err(BundleError::KMismatch { expected, found: self.k });
+        }
+
+        if self.public_inputs.len() != FIBONACCI_PUBLIC_INPUTS {
+            return Err(BundleError::PublicInputCount {
+                expected: FIBONACCI_PUBLIC_INPUTS,
+                found: self.public_inputs.len(),
+            });
+        }
+
+        if self.vk_fingerprint != vk_fingerprint(vk) {
+            return Err(BundleError::FingerprintMismatch);
+        }
+
+        Ok(())
+    }
+
+    /// Writes this bundle in the current format version.
+    ///
+    /// Fails with [`io::ErrorKind::InvalidInput`] if a field exceeds the limits
+    /// that [`ProofBundle::read`] enforces.
+    pub fn write<W: Write>(&self, writer: &mut W) -> io::Result<()> {
+        let circuit_id = self.circuit_id.as_bytes();
+        let circuit_id_len = u8::try_from(circuit_id.len())
+            .map_err(|_| io::Error::new(io::ErrorKind::InvalidInput, "circuit id too long"))?;
+        let k = encode_len("k", self.k as usize, MAX_K as usize)?;
+        let public_inputs_len = encode_len("public inputs", self.public_inputs.len(), MAX_PUBLIC_INPUTS)?;
+        let proof_len = encode_len("proof", self.proof.len(), MAX_PROOF_LEN)?;
+
+        writer.write_all(&MAGIC)?;
+        writer.write_all(&VERSION.to_le_bytes())?;
+        writer.write_all(&[circuit_id_len])?;
+        writer.write_all(circuit_id)?;
+        writer.write_all(&k)?;
+        writer.write_all(&self.n.to_le_bytes())?;
+
+        writer.write_all(&public_inputs_len)?;
+        for input in &self.public_inputs {
+            writer.write_all(input.to_repr().as_ref())?;
+        }
+
+        writer.write_all(&proof_len)?;
+        writer.write_all(&self.proof)?;
+
+        writer.write_all(&self.vk_fingerprint)
+    }
+
+    /// Reads a bundle, rejecting unknown versions and malformed fields.
+    pub fn read<R: Read>(reader: &mut R) -> Result<Self, BundleError> {
+        if read_array::<_, 4>(reader)? != MAGIC {
+            return Err(BundleError::BadMagic);
+        }
+
+        let version = u16::from_le_bytes(read_array(reader)?);
+        if version != VERSION {
+            return Err(BundleError::UnsupportedVersion(version));
+        }
+
+        let [circuit_id_len] = read_array(reader)?;
+        let circuit_id = String::from_utf8(read_vec(reader, circuit_id_len as usize)?)
+            .map_err(|e| BundleError::UnknownCircuit(String::from_utf8_lossy(e.as_bytes()).into_owned()))?;
+
+        let k = u32::from_le_bytes(read_array(reader)?);
+        if k > MAX_K {
+            return Err(BundleError::TooLarge { field: "k", len: k as usize });
+        }
+        let n = u64::from_le_bytes(read_array(reader)?);
+
+        let count = read_len(reader, "public inputs", MAX_PUBLIC_INPUTS)?;
+        let public_inputs = (0..count)
+            .map(|i| {
+                let repr = read_array(reader)?;
+                Option::from(Fp::from_repr(repr)).ok_or(BundleError::InvalidPublicInput(i))
+            })
+            .collect::<Result<_, _>>()?;
+
+        let proof_len = read_len(reader, "proof", MAX_PROOF_LEN)?;
+        let proof = read_vec(reader, proof_len)?;
+
+        let vk_fingerprint = read_array(reader)?;
+
+        Ok(ProofBundle {
+            circuit_id,
+            k,
+            n,
+            public_inputs,
+            proof,
+            vk_fingerprint,
+        })
+    }
+}
+
+fn read_array<R: Read, const N: usize>(reader: &mut R) -> io::Result<[u8; N]> {
+    let mut bytes = [0; N];
+    reader.read_exact(&mut bytes)?;
+    Ok(bytes)
+}
+
+fn read_vec<R: Read>(reader: &mut R, len: usize) -> io::Result<Vec<u8>> {
+    let mut bytes = vec![0; len];
+    reader.read_exact(&mut bytes)?;
+    Ok(bytes)
+}
+
+fn read_len<R: Read>(reader: &mut R, field: &'static str, max: usize) -> Result<usize, BundleError> {
+    let len = u32::from_le_bytes(read_array(reader)?) as usize;
+    if len > max {
+        return Err(BundleError::TooLarge { field, len });
+    }
+    Ok(len)
+}
+
+fn encode_len(field: &'static str, len: usize, max: usize) -> io::Result<[u8; 4]> {
+    let encoded = u32::try_from(len)
+        .ok()
+        .filter(|_| len <= max)
+        .ok_or_else(|| io::Error::new(io::ErrorKind::InvalidInput, format!("{} too large ({})", field, len)))?;
+    Ok(encoded.to_le_bytes())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::proof::{keygen, prove, setup, verify};
+
+    fn bundle(n: usize) -> (Params<EqAffine>, VerifyingKey<EqAffine>, ProofBundle) {
+        let params = setup(n);
+        let pk = keygen(&params, n).unwrap();
+        let proof = prove(&params, &pk, n, (Fp::zero(), Fp::one())).unwrap();
+        let bundle = ProofBundle::new(&params, pk.get_vk(), &proof);
+        (params, pk.get_vk().clone(), bundle)
+    }
+
+    fn roundtrip(bundle: &ProofBundle) -> Result<ProofBundle, BundleError> {
+        let mut bytes = vec![];
+        bundle.write(&mut bytes).unwrap();
+        ProofBundle::read(&mut &bytes[..])
+    }
+
+    #[test]
+    fn test_roundtrip() {
+        let (params, vk, bundle) = bundle(10);
+
+        let read = roundtrip(&bundle).unwrap();
+        assert_eq!(read, bundle);
+
+        read.validate(&params, &vk).unwrap();
+        let proof = read.to_proof().unwrap();
+        verify(&params, &vk, &read.public_inputs, &proof).unwrap();
+    }
+
+    #[test]
+    fn test_malformed() {
+        let (_, _, bundle) = bundle(10);
+        let mut bytes = vec![];
+        bundle.write(&mut bytes).unwrap();
+
+        let mut bad_magic = bytes.clone();
+        bad_magic[0] = b'X';
+        assert!(matches!(ProofBundle::read(&mut &bad_magic[..]), Err(BundleError::BadMagic)));
+
+        let mut bad_version = bytes.clone();
+        bad_version[4] = 2;
+        assert!(matches!(
+            ProofBundle::read(&mut &bad_version[..]),
+            Err(BundleError::UnsupportedVersion(2))
+        ));
+
+        let mut large_k = bytes.clone();
+        let offset = 4 + 2 + 1 + FIBONACCI_CIRCUIT_ID.len();
+        large_k[offset..offset + 4].copy_from_slice(&(MAX_K + 1).to_le_bytes());
+        assert!(matches!(
+            ProofBundle::read(&mut &large_k[..]),
+            Err(BundleError::TooLarge { field: "k", .. })
+        ));
+
+        let truncated = &bytes[..bytes.len() - 1];
+        assert!(matches!(ProofBundle::read(&mut &truncated[..]), Err(BundleError::Io(_))));
+
+        let mut non_canonical = bundle.clone();
+        non_canonical.public_inputs.clear();
+        let mut bytes = vec![];
+        non_canonical.write(&mut bytes).unwrap();
+        // Replace the (now empty) public inputs with one all-ones element.
+        let offset = 4 + 2 + 1 + FIBONACCI_CIRCUIT_ID.len() + 4 + 8;
+        bytes[offset] = 1;
+        bytes.splice(offset + 4..offset + 4, [0xff; 32]);
+        assert!(matches!(
+            ProofBundle::read(&mut &bytes[..]),
+            Err(BundleError::InvalidPublicInput(0))
+        ));
+    }
+
+    #[test]
+    fn test_validate_rejects_other_circuits() {
+        let (params, vk, bundle) = bundle(10);
+
+        let mut other_id = bundle.clone();
+        other_id.circuit_id = "tribonacci".to_string();
+        assert!(matches!(other_id.validate(&params, &vk), Err(BundleError::UnknownCircuit(_))));
+
+        let mut other_k = bundle.clone();
+        other_k.k += 1;
+        assert!(matches!(other_k.validate(&params, &vk), Err(BundleError::KMismatch { .. })));
+
+        // Same k, different n: the verifying key differs.
+        let other_vk = keygen(&params, 9).unwrap().get_vk().clone();
+        assert!(matches!(
+            bundle.validate(&params, &other_vk),
+            Err(BundleError::FingerprintMismatch)
+        ));
+    }
+
+    #[test]
+    fn test_write_rejects_what_read_would() {
+        let (_, _, bundle) = bundle(10);
+
+        let mut large_proof = bundle.clone();
+        large_proof.proof = vec![0; MAX_PROOF_LEN + 1];
+        let mut many_inputs = bundle.clone();
+        many_inputs.public_inputs = vec![Fp::zero(); MAX_PUBLIC_INPUTS + 1];
+        let mut large_k = bundle.clone();
+        large_k.k = MAX_K + 1;
+
+        for bundle in [large_proof, many_inputs, large_k] {
+            let mut bytes = vec![];
+            assert_eq!(bundle.write(&mut bytes).unwrap_err().kind(), io::ErrorKind::InvalidInput);
+            assert!(bytes.is_empty());
+        }
+    }
+
+    #[test]
+    fn test_to_proof_needs_every_public_input() {
+        let (_, _, bundle) = bundle(10);
+        assert_eq!(bundle.to_proof().unwrap().seeds, (Fp::zero(), Fp::one()));
+
+        let mut missing = bundle;
+        missing.public_inputs.pop();
+        assert!(matches!(
+            missing.to_proof(),
+            Err(BundleError::PublicInputCount { expected: 3, found: 2 })
+        ));
+    }
+}
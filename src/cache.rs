@@ -0,0 +1,179 @@
+//! An on-disk cache of IPA parameters and verifying-key fingerprints.
+//!
+//! `halo2_proofs` can serialize [`Params`] but not proving keys, so only the
+//! parameters are stored, in `params-k{k}.bin`. Proving keys are regenerated
+//! from them, which is deterministic, and checked against the fingerprint
+//! recorded in `fibonacci-k{k}-n{n}.vk` the first time the keys for that
+//! shape were generated. A mismatch means the gate definition changed since
+//! the cache was written, so proofs made with the old keys will no longer
+//! verify.
+
+use std::fs::{self, File};
+use std::io::{BufReader, BufWriter, Write};
+use std::path::{Path, PathBuf};
+
+use halo2_proofs::circuit::Value;
+use halo2_proofs::pasta::{EqAffine, Fp};
+use halo2_proofs::plonk::{Error, ProvingKey};
+use halo2_proofs::poly::commitment::Params;
+
+use crate::bundle::{vk_fingerprint, FIBONACCI_CIRCUIT_ID};
+use crate::circuit::FibonacciCircuit;
+use crate::proof::{self, keygen};
+
+/// How [`KeyCache::load`] found the cache for a circuit shape.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum CacheStatus {
+    /// Nothing was cached for this shape.
+    Miss,
+    /// The regenerated keys match the cached fingerprint.
+    Hit,
+    /// The regenerated keys differ from the cached fingerprint, which has been
+    /// replaced.
+    Stale,
+}
+
+/// Parameters and keys for one circuit shape.
+#[derive(Debug)]
+pub struct CachedKeys {
+    pub params: Params<EqAffine>,
+    pub pk: ProvingKey<EqAffine>,
+    pub status: CacheStatus,
+}
+
+/// A directory holding cached parameters and fingerprints.
+#[derive(Clone, Debug)]
+pub struct KeyCache {
+    dir: PathBuf,
+}
+
+impl KeyCache {
+    /// Uses `dir` as the cache directory, creating it when first written to.
+    pub fn new(dir: impl Into<PathBuf>) -> Self {
+        KeyCache { dir: dir.into() }
+    }
+
+    /// The cache directory.
+    pub fn dir(&self) -> &Path {
+        &self.dir
+    }
+
+    fn params_path(&self, k: u32) -> PathBuf {
+        self.dir.join(format!("params-k{}.bin", k))
+    }
+
+    fn fingerprint_path(&self, k: u32, n: usize) -> PathBuf {
+        self.dir.join(format!("{}-k{}-n{}.vk", FIBONACCI_CIRCUIT_ID, k, n))
+    }
+
+    /// Reads the parameters for `k`, generating and caching them if they are
+    /// missing, corrupt or were written for a different `k`.
+    pub fn params(&self, k: u32) -> Result<Params<EqAffine>, Error> {
+        let path = self.params_path(k);
+
+        if let Some(params) = read_params(&path, k) {
+            return Ok(params);
+        }
+
+        let params = Params::new(k);
+        fs::create_dir_all(&self.dir)?;
+        let mut writer = BufWriter::new(File::create(&path)?);
+        params.write(&mut writer)?;
+        writer.flush()?;
+
+        Ok(params)
+    }
+
+    /// Loads the parameters and keys for the circuit proving the n-th term.
+    pub fn load(&self, n: usize) -> Result<CachedKeys, Error> {
+        let k = FibonacciCircuit::<Fp>::new(n, Value::unknown(), Value::unknown()).k();
+        let params = self.params(k)?;
+        let pk = keygen(&params, n)?;
+
+        let fingerprint = vk_fingerprint(pk.get_vk());
+        let path = self.fingerprint_path(k, n);
+        let status = match fs::read(&path) {
+            Ok(cached) if cached == fingerprint => CacheStatus::Hit,
+            Ok(_) => CacheStatus::Stale,
+            Err(_) => CacheStatus::Miss,
+        };
+        if status != CacheStatus::Hit {
+            fs::write(&path, fingerprint)?;
+        }
+
+        Ok(CachedKeys { params, pk, status })
+    }
+}
+
+/// Reads the parameters at `path`, if they exist and are for `k`.
+fn read_params(path: &Path, k: u32) -> Option<Params<EqAffine>> {
+    let mut reader = BufReader::new(File::open(path).ok()?);
+    proof::read_params(&mut reader, k).ok()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::proof::{params_k, prove, verify};
+
+    fn temp_cache(name: &str) -> KeyCache {
+        let dir = std::env::temp_dir().join(format!("halo2-hope-{}-{}", name, std::process::id()));
+        let _ = fs::remove_dir_all(&dir);
+        KeyCache::new(dir)
+    }
+
+    #[test]
+    fn test_cache() {
+        let cache = temp_cache("cache");
+
+        let first = cache.load(10).unwrap();
+        assert_eq!(first.status, CacheStatus::Miss);
+
+        let second = cache.load(10).unwrap();
+        assert_eq!(second.status, CacheStatus::Hit);
+
+        // Keys regenerated from cached parameters verify proofs made with the
+        // first ones.
+        let proof = prove(&first.params, &first.pk, 10, (Fp::zero(), Fp::one())).unwrap();
+        verify(&second.params, second.pk.get_vk(), &proof.public_inputs(), &proof).unwrap();
+
+        // Another shape with the same k reuses the parameters.
+        assert_eq!(cache.load(9).unwrap().status, CacheStatus::Miss);
+
+        fs::remove_dir_all(cache.dir()).unwrap();
+    }
+
+    #[test]
+    fn test_stale_cache() {
+        let cache = temp_cache("stale");
+
+        cache.load(10).unwrap();
+
+        // Simulate keys generated by an older gate definition.
+        let k = FibonacciCircuit::<Fp>::new(10, Value::unknown(), Value::unknown()).k();
+        fs::write(cache.fingerprint_path(k, 10), [0; 32]).unwrap();
+
+        assert_eq!(cache.load(10).unwrap().status, CacheStatus::Stale);
+        assert_eq!(cache.load(10).unwrap().status, CacheStatus::Hit);
+
+        fs::remove_dir_all(cache.dir()).unwrap();
+    }
+
+    #[test]
+    fn test_corrupt_params() {
+        let cache = temp_cache("corrupt");
+
+        cache.params(4).unwrap();
+        fs::write(cache.params_path(4), b"not params").unwrap();
+
+        assert_eq!(params_k(&cache.params(4).unwrap()), 4);
+        assert!(read_params(&cache.params_path(4), 4).is_some());
+
+        // Parameters for another k are regenerated rather than trusted.
+        fs::copy(cache.params_path(4), cache.params_path(5)).unwrap();
+        assert!(read_params(&cache.params_path(5), 5).is_none());
+        assert_eq!(params_k(&cache.params(5).unwrap()), 5);
+
+        fs::remove_dir_all(cache.dir()).unwrap();
+    }
+}
@@ -0,0 +1,322 @@
+/*
+
+    Proves the n-th term of the sequence seeded with (x_0, x_1):
+
+    | elem_1  | elem_2  | elem_3  | q_fib | instance
+    ------------------------------------------------
+    |   x_0   |   x_1   |   x_2   |   1   |   x_n
+    |   x_1   |   x_2   |   x_3   |   1   |   x_0
+    |   ...   |   ...   |   ...   |   1   |   x_1
+    | x_{n-2} | x_{n-1} |   x_n   |   1   |
+
+    That is one `init` row followed by n - 2 `assign` rows. Other layouts of
+    the same chain implement `FibonacciLayout`.
+
+    The seeds are public: x_n = F(n - 1) * x_0 + F(n) * x_1, so with private
+    seeds a prover could reach any x_n and the proof would say nothing.
+
+*/
+
+use std::marker::PhantomData;
+
+use halo2_proofs::arithmetic::FieldExt;
+use halo2_proofs::circuit::{AssignedCell, Chip, Layouter, SimpleFloorPlanner, Value};
+use halo2_proofs::plonk::*;
+
+use crate::fibonacci::{ChainCells, FibonacciChip, FibonacciConfig};
+
+/// Computes the n-th term of the sequence seeded with `(elem_1, elem_2)`
+/// natively, as the value the circuit exposes in its instance column.
+pub fn nth_term<F: FieldExt>(n: usize, elem_1: F, elem_2: F) -> F {
+    let (mut a, mut b) = (elem_1, elem_2);
+    for _ in 0..n {
+        let c = a + b;
+        a = b;
+        b = c;
+    }
+    a
+}
+
+/// The public inputs of [`LayoutCircuit`] for the n-th term of the sequence
+/// seeded with `(elem_1, elem_2)`: the n-th term followed by the seeds.
+pub fn public_inputs<F: FieldExt>(n: usize, elem_1: F, elem_2: F) -> Vec<F> {
+    vec![nth_term(n, elem_1, elem_2), elem_1, elem_2]
+}
+
+/// Returns the smallest `k` such that `rows` rows fit in a circuit with the
+/// constraint system `cs`, after reserving the rows needed for blinding.
+pub fn min_k<F: FieldExt>(rows: usize, cs: &ConstraintSystem<F>) -> u32 {
+    let needed = std::cmp::max(rows + cs.blinding_factors() + 1, cs.minimum_rows());
+    needed.next_power_of_two().trailing_zeros()
+}
+
+/// A way of laying out the chain x_0, ..., x_n in a circuit.
+///
+/// [`FibonacciChip`] opens one region per step, so each step is a
+/// self-contained gadget that can be composed with other chips. The
+/// rotation-based layouts, such as
+/// [`TwoColumnChip`](crate::two_column::TwoColumnChip) and
+/// [`SingleColumnChip`](crate::single_column::SingleColumnChip), trade that
+/// modularity for fewer columns and copy constraints per row.
+pub trait FibonacciLayout<F: FieldExt>: Chip<F> {
+    /// Allocates the columns of the layout and creates its gate.
+    fn configure(meta: &mut ConstraintSystem<F>) -> Self::Config;
+
+    /// Constructs the chip from a config returned by
+    /// [`FibonacciLayout::configure`].
+    fn construct(config: Self::Config) -> Self;
+
+    /// The number of rows used to lay out x_0 to x_n.
+    fn rows(n: usize) -> usize;
+
+    /// Assigns x_0 to x_n of the sequence seeded with `elem_1` and `elem_2`,
+    /// returning the cells holding x_0, x_1 and x_n.
+    fn assign_nth_term(
+        &self,
+        layouter: impl Layouter<F>,
+        n: usize,
+        elem_1: Value<F>,
+        elem_2: Value<F>,
+    ) -> Result<ChainCells<F>, Error>;
+
+    /// Constrains `cell` to equal the given `row` of the instance column.
+    fn expose_public(&self, layouter: impl Layouter<F>, cell: &AssignedCell<F, F>, row: usize) -> Result<(), Error>;
+}
+
+impl<F: FieldExt> FibonacciLayout<F> for FibonacciChip<F> {
+    fn configure(meta: &mut ConstraintSystem<F>) -> FibonacciConfig {
+        FibonacciChip::configure(meta)
+    }
+
+    fn construct(config: FibonacciConfig) -> Self {
+        FibonacciChip::construct(config)
+    }
+
+    fn rows(n: usize) -> usize {
+        n - 1
+    }
+
+    fn assign_nth_term(
+        &self,
+        mut layouter: impl Layouter<F>,
+        n: usize,
+        elem_1: Value<F>,
+        elem_2: Value<F>,
+    ) -> Result<ChainCells<F>, Error> {
+        let (seed_1, (mut elem_2, mut elem_3)) = self.init_with_seeds(layouter.namespace(|| "init"), elem_1, elem_2)?;
+        let seed_2 = elem_2.clone();
+        for i in 3..=n {
+            (elem_2, elem_3) = self.assign(layouter.namespace(|| format!("x_{}", i)), elem_2, elem_3)?;
+        }
+        Ok((seed_1, seed_2, elem_3))
+    }
+
+    fn expose_public(&self, layouter: impl Layouter<F>, cell: &AssignedCell<F, F>, row: usize) -> Result<(), Error> {
+        FibonacciChip::expose_public(self, layouter, cell, row)
+    }
+}
+
+/// A circuit proving the n-th term of a Fibonacci-style sequence with the
+/// layout `L`, exposing it in row 0 of the instance column and the seeds in
+/// rows 1 and 2.
+#[derive(Clone, Debug)]
+pub struct LayoutCircuit<F: FieldExt, L> {
+    n: usize,
+    elem_1: Value<F>,
+    elem_2: Value<F>,
+    _layout: PhantomData<L>,
+}
+
+/// The circuit with one region per Fibonacci step.
+pub type FibonacciCircuit<F> = LayoutCircuit<F, FibonacciChip<F>>;
+
+impl<F: FieldExt, L> Default for LayoutCircuit<F, L> {
+    fn default() -> Self {
+        Self {
+            n: 0,
+            elem_1: Value::unknown(),
+            elem_2: Value::unknown(),
+            _layout: PhantomData,
+        }
+    }
+}
+
+impl<F: FieldExt, L: FibonacciLayout<F>> LayoutCircuit<F, L> {
+    /// Creates a circuit for the n-th term of the sequence seeded with
+    /// `(elem_1, elem_2)`, which must have `n >= 2`.
+    pub fn new(n: usize, elem_1: Value<F>, elem_2: Value<F>) -> Self {
+        assert!(n >= 2, "the circuit has at least one Fibonacci step");
+        Self {
+            n,
+            elem_1,
+            elem_2,
+            _layout: PhantomData,
+        }
+    }
+
+    /// The index of the term this circuit proves.
+    pub fn n(&self) -> usize {
+        self.n
+    }
+
+    /// The number of rows used by the chain.
+    pub fn rows(&self) -> usize {
+        L::rows(self.n)
+    }
+
+    /// The smallest `k` for which this circuit fits in 2^k rows.
+    pub fn k(&self) -> u32 {
+        let mut cs = ConstraintSystem::default();
+        Self::configure(&mut cs);
+        // The instance column holds the output and the two seeds.
+        min_k(std::cmp::max(self.rows(), 3), &cs)
+    }
+}
+
+impl<F: FieldExt, L: FibonacciLayout<F>> Circuit<F> for LayoutCircuit<F, L> {
+    type Config = L::Config;
+
+    type FloorPlanner = SimpleFloorPlanner;
+
+    fn without_witnesses(&self) -> Self {
+        Self {
+            n: self.n,
+            ..Self::default()
+        }
+    }
+
+    fn configure(meta: &mut ConstraintSystem<F>) -> Self::Config {
+        L::configure(meta)
+    }
+
+    fn synthesize(&self, config: Self::Config, mut layouter: impl Layouter<F>) -> Result<(), Error> {
+        let chip = L::construct(config);
+
+        let (elem_1, elem_2, out) = chip.assign_nth_term(layouter.namespace(|| "chain"), self.n, self.elem_1, self.elem_2)?;
+        chip.expose_public(layouter.namespace(|| "out"), &out, 0)?;
+        chip.expose_public(layouter.namespace(|| "elem_1"), &elem_1, 1)?;
+        chip.expose_public(layouter.namespace(|| "elem_2"), &elem_2, 2)
+    }
+}
+
+/// A circuit cross-checking other gadgets for F(n) against the linear chain.
+#[cfg(test)]
+pub(crate) mod cross_check {
+    use std::marker::PhantomData;
+
+    use halo2_proofs::circuit::{AssignedCell, Layouter, SimpleFloorPlanner, Value};
+    use halo2_proofs::pasta::Fp;
+    use halo2_proofs::plonk::{Circuit, ConstraintSystem, Error};
+
+    use super::FibonacciLayout;
+    use crate::fibonacci::{FibonacciChip, FibonacciConfig};
+
+    /// A gadget proving F(n) other than the linear chain.
+    pub(crate) trait NthFibonacci {
+        type Config: Clone;
+
+        fn configure(meta: &mut ConstraintSystem<Fp>) -> Self::Config;
+
+        /// Assigns F(n), returning the cell holding it.
+        fn assign_nth_term(
+            config: Self::Config,
+            layouter: impl Layouter<Fp>,
+            n: usize,
+        ) -> Result<AssignedCell<Fp, Fp>, Error>;
+    }
+
+    /// Proves F(n) with both the linear chain and `G`, constraining the two
+    /// outputs to be equal.
+    pub(crate) struct CrossCheckCircuit<G> {
+        n: usize,
+        _gadget: PhantomData<G>,
+    }
+
+    impl<G> CrossCheckCircuit<G> {
+        pub(crate) fn new(n: usize) -> Self {
+            Self { n, _gadget: PhantomData }
+        }
+    }
+
+    impl<G: NthFibonacci> Circuit<Fp> for CrossCheckCircuit<G> {
+        type Config = (FibonacciConfig, G::Config);
+
+        type FloorPlanner = SimpleFloorPlanner;
+
+        fn without_witnesses(&self) -> Self {
+            Self::new(self.n)
+        }
+
+        fn configure(meta: &mut ConstraintSystem<Fp>) -> Self::Config {
+            (FibonacciChip::configure(meta), G::configure(meta))
+        }
+
+        fn synthesize(&self, config: Self::Config, mut layouter: impl Layouter<Fp>) -> Result<(), Error> {
+            let linear = FibonacciChip::construct(config.0);
+
+            let (seed_0, seed_1) = (Value::known(Fp::zero()), Value::known(Fp::one()));
+            let (_, _, expected) = linear.assign_nth_term(layouter.namespace(|| "linear chain"), self.n, seed_0, seed_1)?;
+            let output = G::assign_nth_term(config.1, layouter.namespace(|| "gadget"), self.n)?;
+
+            layouter.assign_region(|| "outputs agree", |mut region| region.constrain_equal(expected.cell(), output.cell()))
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use halo2_proofs::{dev::MockProver, pasta::Fp};
+
+    use super::*;
+
+    #[test]
+    fn test_nth_term() {
+        let terms: Vec<_> = (0..8).map(|n| nth_term(n, Fp::zero(), Fp::one())).collect();
+        let expected: Vec<_> = [0, 1, 1, 2, 3, 5, 8, 13].into_iter().map(Fp::from).collect();
+        assert_eq!(terms, expected);
+    }
+
+    #[test]
+    fn test_fibonacci_circuit() {
+        let circuit = FibonacciCircuit::new(10, Value::known(Fp::zero()), Value::known(Fp::one()));
+
+        let prover = MockProver::run(circuit.k(), &circuit, vec![vec![Fp::from(55), Fp::zero(), Fp::one()]]).unwrap();
+        prover.assert_satisfied();
+    }
+
+    #[test]
+    fn test_public_seeds() {
+        // x_10 = 34 * x_0 + 55 * x_1, so (355, 5) also reaches 12345.
+        let circuit = FibonacciCircuit::new(10, Value::known(Fp::from(355)), Value::known(Fp::from(5)));
+        let inputs = public_inputs(10, Fp::from(355), Fp::from(5));
+        assert_eq!(inputs[0], Fp::from(12345));
+
+        let prover = MockProver::run(circuit.k(), &circuit, vec![inputs]).unwrap();
+        prover.assert_satisfied();
+
+        // The output cannot be passed off as coming from other seeds.
+        let prover = MockProver::run(circuit.k(), &circuit, vec![vec![Fp::from(12345), Fp::zero(), Fp::one()]]).unwrap();
+        assert!(prover.verify().is_err());
+    }
+
+    #[test]
+    fn test_many_n() {
+        for n in 2..=100 {
+            let circuit = FibonacciCircuit::new(n, Value::known(Fp::one()), Value::known(Fp::one()));
+            let inputs = public_inputs(n, Fp::one(), Fp::one());
+
+            let prover = MockProver::run(circuit.k(), &circuit, vec![inputs]).unwrap();
+            prover.assert_satisfied();
+        }
+    }
+
+    #[test]
+    fn test_k_is_minimal() {
+        for n in [2, 10, 11, 12, 26, 27, 28, 100, 1000] {
+            let circuit = FibonacciCircuit::new(n, Value::known(Fp::one()), Value::known(Fp::one()));
+            let inputs = public_inputs(n, Fp::one(), Fp::one());
+
+            assert!(MockProver::run(circuit.k() - 1, &circuit, vec![inputs]).is_err());
+        }
+    }
+}
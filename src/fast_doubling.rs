@@ -0,0 +1,285 @@
+/*
+
+    Fast doubling over the bits of n, most significant first. Each row holds
+    (F(m), F(m + 1)) for the prefix m of n read so far:
+
+        F(2m)     = F(m) * (2 * F(m + 1) - F(m))
+        F(2m + 1) = F(m)^2 + F(m + 1)^2
+
+    e.g. n = 11 = 0b1011:
+
+    |  a   |  b   | bit | acc | q_double
+    ------------------------------------
+    |  0   |  1   |  1  |  0  |    1
+    |  1   |  1   |  0  |  1  |    1
+    |  1   |  2   |  1  |  2  |    1
+    |  5   |  8   |  1  |  5  |    1
+    |  89  | 144  |     | 11  |    0
+
+    With c = a * (2b - a) and d = a^2 + b^2:
+
+    q_double * bit * (1 - bit) = 0
+    q_double * (c + bit * (d - c) - a_next) = 0
+    q_double * (d + bit * c - b_next) = 0
+    q_double * (2 * acc + bit - acc_next) = 0
+
+    The first row is fixed to (0, 1, 0), so the last row holds F(n) and n.
+
+*/
+
+use std::marker::PhantomData;
+
+use halo2_proofs::arithmetic::FieldExt;
+use halo2_proofs::circuit::{AssignedCell, Chip, Layouter, Value};
+use halo2_proofs::plonk::*;
+use halo2_proofs::poly::Rotation;
+
+/// Computes `(F(n), F(n + 1))` natively by fast doubling.
+pub fn fast_doubling<F: FieldExt>(n: u64) -> (F, F) {
+    (0..64).rev().fold((F::zero(), F::one()), |(a, b), i| {
+        let c = a * (b.double() - a);
+        let d = a.square() + b.square();
+        if (n >> i) & 1 == 1 {
+            (d, c + d)
+        } else {
+            (c, d)
+        }
+    })
+}
+
+/// The cells holding `(F(n), n)` after the last bit of n.
+pub type FastDoublingCells<F> = (AssignedCell<F, F>, AssignedCell<F, F>);
+
+/// Columns and selector used by the `fast doubling` gate.
+#[derive(Clone, Debug, Copy)]
+pub struct FastDoublingConfig {
+    pub a: Column<Advice>,
+    pub b: Column<Advice>,
+    pub bit: Column<Advice>,
+    pub acc: Column<Advice>,
+    pub constants: Column<Fixed>,
+    pub q_double: Selector,
+    pub instance: Column<Instance>,
+}
+
+/// A chip proving F(n) in one row per bit of n.
+#[derive(Clone, Debug)]
+pub struct FastDoublingChip<F: FieldExt> {
+    config: FastDoublingConfig,
+    _marker: PhantomData<F>,
+}
+
+impl<F: FieldExt> Chip<F> for FastDoublingChip<F> {
+    type Config = FastDoublingConfig;
+    type Loaded = ();
+
+    fn config(&self) -> &Self::Config {
+        &self.config
+    }
+
+    fn loaded(&self) -> &Self::Loaded {
+        &()
+    }
+}
+
+impl<F: FieldExt> FastDoublingChip<F> {
+    /// Constructs a chip from a config returned by
+    /// [`FastDoublingChip::configure`].
+    pub fn construct(config: FastDoublingConfig) -> Self {
+        Self {
+            config,
+            _marker: PhantomData,
+        }
+    }
+
+    /// Allocates four advice columns, a constants column, a selector and an
+    /// instance column, and creates the `fast doubling` gate over them.
+    pub fn configure(cs: &mut ConstraintSystem<F>) -> FastDoublingConfig {
+        let a = cs.advice_column();
+        cs.enable_equality(a);
+        let b = cs.advice_column();
+        cs.enable_equality(b);
+        let bit = cs.advice_column();
+        let acc = cs.advice_column();
+        cs.enable_equality(acc);
+        let constants = cs.fixed_column();
+        cs.enable_constant(constants);
+        let q_double = cs.selector();
+        let instance = cs.instance_column();
+        cs.enable_equality(instance);
+
+        cs.create_gate("fast doubling", |virtual_cells| {
+            let q_double = virtual_cells.query_selector(q_double);
+            let a_cur = virtual_cells.query_advice(a, Rotation::cur());
+            let b_cur = virtual_cells.query_advice(b, Rotation::cur());
+            let bit = virtual_cells.query_advice(bit, Rotation::cur());
+            let acc_cur = virtual_cells.query_advice(acc, Rotation::cur());
+            let a_next = virtual_cells.query_advice(a, Rotation::next());
+            let b_next = virtual_cells.query_advice(b, Rotation::next());
+            let acc_next = virtual_cells.query_advice(acc, Rotation::next());
+
+            let one = Expression::Constant(F::one());
+            let two = Expression::Constant(F::from(2));
+
+            // F(2m) and F(2m + 1)
+            let c = a_cur.clone() * (two.clone() * b_cur.clone() - a_cur.clone());
+            let d = a_cur.clone() * a_cur + b_cur.clone() * b_cur;
+
+            Constraints::with_selector(
+                q_double,
+                [
+                    ("bit is boolean", bit.clone() * (one - bit.clone())),
+                    ("a_next", c.clone() + bit.clone() * (d.clone() - c.clone()) - a_next),
+                    ("b_next", d + bit.clone() * c - b_next),
+                    ("acc_next", two * acc_cur + bit - acc_next),
+                ],
+            )
+        });
+
+        FastDoublingConfig { a, b, bit, acc, constants, q_double, instance }
+    }
+
+    /// Assigns one row per bit of the low `num_bits` bits of `n`, returning
+    /// the cells holding F(n) and n.
+    pub fn assign(
+        &self,
+        mut layouter: impl Layouter<F>,
+        n: Value<u64>,
+        num_bits: usize,
+    ) -> Result<FastDoublingCells<F>, Error> {
+        let config = self.config();
+        assert!(num_bits <= 64, "n is a u64");
+        n.error_if_known_and(|n| num_bits < 64 && n >> num_bits != 0)?;
+
+        layouter.assign_region(|| "fast doubling", |mut region| {
+            // The starting state (F(0), F(1)) for the empty prefix
+            let mut a = region.assign_advice_from_constant(|| "F(0)", config.a, 0, F::zero())?;
+            let mut b = region.assign_advice_from_constant(|| "F(1)", config.b, 0, F::one())?;
+            let mut acc = region.assign_advice_from_constant(|| "empty prefix", config.acc, 0, F::zero())?;
+
+            for (offset, i) in (0..num_bits).rev().enumerate() {
+                // Enable q_double
+                config.q_double.enable(&mut region, offset)?;
+
+                let bit = n.map(|n| (n >> i) & 1 == 1);
+                region.assign_advice(|| format!("bit {}", i), config.bit, offset, || bit.map(F::from))?;
+
+                let c = a.value().zip(b.value()).map(|(a, b)| *a * (b.double() - a));
+                let d = a.value().zip(b.value()).map(|(a, b)| a.square() + b.square());
+                let next = bit.zip(c.zip(d)).map(|(bit, (c, d))| if bit { (d, c + d) } else { (c, d) });
+                let (a_next, b_next) = next.unzip();
+                let acc_next = acc.value().zip(bit).map(|(acc, bit)| acc.double() + F::from(bit));
+
+                a = region.assign_advice(|| "a", config.a, offset + 1, || a_next)?;
+                b = region.assign_advice(|| "b", config.b, offset + 1, || b_next)?;
+                acc = region.assign_advice(|| "acc", config.acc, offset + 1, || acc_next)?;
+            }
+
+            Ok((a, acc))
+        })
+    }
+
+    /// Constrains `cell` to equal the given `row` of the instance column.
+    pub fn expose_public(
+        &self,
+        mut layouter: impl Layouter<F>,
+        cell: &AssignedCell<F, F>,
+        row: usize,
+    ) -> Result<(), Error> {
+        layouter.constrain_instance(cell.cell(), self.config().instance, row)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use halo2_proofs::{circuit::SimpleFloorPlanner, dev::MockProver, pasta::Fp};
+
+    use super::*;
+    use crate::circuit::cross_check::{CrossCheckCircuit, NthFibonacci};
+    use crate::circuit::nth_term;
+
+    const NUM_BITS: usize = 64;
+
+    #[derive(Clone, Default)]
+    struct FastDoublingCircuit {
+        n: Value<u64>,
+    }
+
+    impl Circuit<Fp> for FastDoublingCircuit {
+        type Config = FastDoublingConfig;
+
+        type FloorPlanner = SimpleFloorPlanner;
+
+        fn without_witnesses(&self) -> Self {
+            Self::default()
+        }
+
+        fn configure(meta: &mut ConstraintSystem<Fp>) -> Self::Config {
+            FastDoublingChip::configure(meta)
+        }
+
+        fn synthesize(&self, config: Self::Config, mut layouter: impl Layouter<Fp>) -> Result<(), Error> {
+            let chip = FastDoublingChip::construct(config);
+
+            let (output, n) = chip.assign(layouter.namespace(|| "fast doubling"), self.n, NUM_BITS)?;
+            chip.expose_public(layouter.namespace(|| "F(n)"), &output, 0)?;
+            chip.expose_public(layouter.namespace(|| "n"), &n, 1)
+        }
+    }
+
+    impl NthFibonacci for FastDoublingChip<Fp> {
+        type Config = FastDoublingConfig;
+
+        fn configure(meta: &mut ConstraintSystem<Fp>) -> Self::Config {
+            FastDoublingChip::configure(meta)
+        }
+
+        fn assign_nth_term(
+            config: Self::Config,
+            layouter: impl Layouter<Fp>,
+            n: usize,
+        ) -> Result<AssignedCell<Fp, Fp>, Error> {
+            let (output, _) = FastDoublingChip::construct(config).assign(layouter, Value::known(n as u64), 8)?;
+            Ok(output)
+        }
+    }
+
+    #[test]
+    fn test_native() {
+        for n in 0..100 {
+            assert_eq!(fast_doubling::<Fp>(n).0, nth_term(n as usize, Fp::zero(), Fp::one()));
+        }
+    }
+
+    #[test]
+    fn test_agrees_with_linear_chain() {
+        for n in 2..=40 {
+            let circuit = CrossCheckCircuit::<FastDoublingChip<Fp>>::new(n);
+            let prover = MockProver::run(7, &circuit, vec![vec![], vec![]]).unwrap();
+            prover.assert_satisfied();
+        }
+    }
+
+    #[test]
+    fn test_fast_doubling() {
+        for n in [0, 1, 2, 11, 93, 1 << 40, u64::MAX] {
+            let circuit = FastDoublingCircuit { n: Value::known(n) };
+            let output = fast_doubling::<Fp>(n).0;
+
+            let prover = MockProver::run(7, &circuit, vec![vec![output, Fp::from(n)]]).unwrap();
+            prover.assert_satisfied();
+        }
+    }
+
+    #[test]
+    fn test_wrong_output() {
+        let circuit = FastDoublingCircuit { n: Value::known(11) };
+
+        let prover = MockProver::run(7, &circuit, vec![vec![Fp::from(90), Fp::from(11)]]).unwrap();
+        assert!(prover.verify().is_err());
+
+        // F(11) is not F(12).
+        let prover = MockProver::run(7, &circuit, vec![vec![Fp::from(89), Fp::from(12)]]).unwrap();
+        assert!(prover.verify().is_err());
+    }
+}
@@ -11,32 +11,88 @@
 
     q_fib * (elem_1 + elem_2 - elem_3) = 0
 
+    The last elem_3 is copied into row 0 of the instance column. Seeds
+    assigned with `init` are private and can be chosen to reach any output,
+    so the chain only proves F(n) if its seeds are fixed with `init_standard`
+    or made public, as `circuit::LayoutCircuit` does.
+
 */
 
-use halo2_proofs::circuit::{Value, Layouter, AssignedCell};
+use std::marker::PhantomData;
+
+use halo2_proofs::circuit::{Value, Layouter, AssignedCell, Chip};
 use halo2_proofs::poly::Rotation;
 use halo2_proofs::{plonk::*};
-use halo2_proofs::arithmetic::Field;
+use halo2_proofs::arithmetic::FieldExt;
+
+/// The last two cells of a Fibonacci chain, `(elem_2, elem_3)`.
+///
+/// These are the cells that the next call to [`FibonacciChip::assign`] copies
+/// into its `elem_1` and `elem_2` columns.
+pub type FibonacciCells<F> = (AssignedCell<F, F>, AssignedCell<F, F>);
 
+/// The cells holding the seeds x_0 and x_1 and the last term x_n of a chain.
+pub type ChainCells<F> = (AssignedCell<F, F>, AssignedCell<F, F>, AssignedCell<F, F>);
+
+/// Columns and selector used by the `fibonacci` gate.
 #[derive(Clone, Debug, Copy)]
-struct Config {
-    elem_1: Column<Advice>,
-    elem_2: Column<Advice>,
-    elem_3: Column<Advice>,
-    q_fib: Selector,
+pub struct FibonacciConfig {
+    pub elem_1: Column<Advice>,
+    pub elem_2: Column<Advice>,
+    pub elem_3: Column<Advice>,
+    /// Fixed column holding the seeds of [`FibonacciChip::init_standard`] and
+    /// [`FibonacciChip::init_lucas`].
+    pub constants: Column<Fixed>,
+    pub q_fib: Selector,
+    pub instance: Column<Instance>,
+}
+
+/// A chip proving one Fibonacci step `elem_1 + elem_2 = elem_3` per row.
+#[derive(Clone, Debug)]
+pub struct FibonacciChip<F: FieldExt> {
+    config: FibonacciConfig,
+    _marker: PhantomData<F>,
 }
 
-impl Config {
-    fn configure<F: Field>(
+impl<F: FieldExt> Chip<F> for FibonacciChip<F> {
+    type Config = FibonacciConfig;
+    type Loaded = ();
+
+    fn config(&self) -> &Self::Config {
+        &self.config
+    }
+
+    fn loaded(&self) -> &Self::Loaded {
+        &()
+    }
+}
+
+impl<F: FieldExt> FibonacciChip<F> {
+    /// Constructs a chip from a config returned by [`FibonacciChip::configure`].
+    pub fn construct(config: FibonacciConfig) -> Self {
+        Self {
+            config,
+            _marker: PhantomData,
+        }
+    }
+
+    /// Allocates three equality-enabled advice columns, a constants column, a
+    /// selector and an instance column, and creates the `fibonacci` gate over
+    /// them.
+    pub fn configure(
         cs: &mut ConstraintSystem<F>
-    ) -> Self {
+    ) -> FibonacciConfig {
         let elem_1 = cs.advice_column();
         cs.enable_equality(elem_1);
         let elem_2 = cs.advice_column();
         cs.enable_equality(elem_2);
         let elem_3 = cs.advice_column();
         cs.enable_equality(elem_3);
+        let constants = cs.fixed_column();
+        cs.enable_constant(constants);
         let q_fib = cs.selector();
+        let instance = cs.instance_column();
+        cs.enable_equality(instance);
 
         cs.create_gate("fibonacci", |virtual_cells| {
             let q_fib = virtual_cells.query_selector(q_fib);
@@ -50,33 +106,90 @@ impl Config {
             ]
         });
 
-        Self { elem_1, elem_2, elem_3, q_fib }
+        FibonacciConfig { elem_1, elem_2, elem_3, constants, q_fib, instance }
+    }
+
+    /// Assigns the first row of the chain from the two seeds `elem_1` and
+    /// `elem_2`, returning the cells holding `elem_2` and their sum `elem_3`.
+    pub fn init(
+        &self,
+        layouter: impl Layouter<F>,
+        elem_1: Value<F>,
+        elem_2: Value<F>,
+    ) -> Result<FibonacciCells<F>, Error> {
+        let (_, cells) = self.init_with_seeds(layouter, elem_1, elem_2)?;
+        Ok(cells)
     }
 
-    fn init<F: Field>(
+    /// Like [`FibonacciChip::init`], but also returns the cell holding
+    /// `elem_1`, so that both seeds can be constrained by the caller.
+    pub fn init_with_seeds(
         &self,
         mut layouter: impl Layouter<F>,
         elem_1: Value<F>,
         elem_2: Value<F>,
-    ) -> Result<(
-        AssignedCell<F, F>, // elem_2
-        AssignedCell<F, F> // elem_3
-    ), Error> {
+    ) -> Result<(AssignedCell<F, F>, FibonacciCells<F>), Error> {
+        let config = self.config();
+
         layouter.assign_region(|| "init Fibonacci", |mut region| {
             let offset = 0;
 
             // Enable q_fib
-            self.q_fib.enable(&mut region, offset)?;
+            config.q_fib.enable(&mut region, offset)?;
 
             // Assign elem_1
-            region.assign_advice(|| "elem_1", self.elem_1, offset, || elem_1)?;
+            let elem_1 = region.assign_advice(|| "elem_1", config.elem_1, offset, || elem_1)?;
 
             // Assign elem_2
-            let elem_2 = region.assign_advice(|| "elem_2", self.elem_2, offset, || elem_2)?;
+            let elem_2 = region.assign_advice(|| "elem_2", config.elem_2, offset, || elem_2)?;
 
-            let elem_3 = elem_1 + elem_2.value_field().evaluate();
+            let elem_3 = elem_1.value().copied() + elem_2.value();
             // Assign elem_3
-            let elem_3 = region.assign_advice(|| "elem_3", self.elem_3, offset, || elem_3)?;
+            let elem_3 = region.assign_advice(|| "elem_3", config.elem_3, offset, || elem_3)?;
+
+            Ok((
+                elem_1,
+                (elem_2, elem_3)
+            ))
+
+        })
+    }
+
+    /// Like [`FibonacciChip::init`], but with the seeds 0 and 1 fixed in the
+    /// circuit, so that the n-th term of the chain is F(n).
+    pub fn init_standard(&self, layouter: impl Layouter<F>) -> Result<FibonacciCells<F>, Error> {
+        self.init_constant(layouter, F::zero(), F::one())
+    }
+
+    /// Like [`FibonacciChip::init`], but with the seeds 2 and 1 fixed in the
+    /// circuit, so that the n-th term of the chain is the Lucas number L(n).
+    pub fn init_lucas(&self, layouter: impl Layouter<F>) -> Result<FibonacciCells<F>, Error> {
+        self.init_constant(layouter, F::from(2), F::one())
+    }
+
+    /// Like [`FibonacciChip::init`], but with the seeds fixed in the circuit
+    /// instead of witnessed by the prover.
+    pub fn init_constant(
+        &self,
+        mut layouter: impl Layouter<F>,
+        elem_1: F,
+        elem_2: F,
+    ) -> Result<FibonacciCells<F>, Error> {
+        let config = self.config();
+
+        layouter.assign_region(|| "init constant Fibonacci", |mut region| {
+            let offset = 0;
+
+            // Enable q_fib
+            config.q_fib.enable(&mut region, offset)?;
+
+            // Assign elem_1 and elem_2 from the constants column
+            region.assign_advice_from_constant(|| "elem_1", config.elem_1, offset, elem_1)?;
+            let elem_2 = region.assign_advice_from_constant(|| "elem_2", config.elem_2, offset, elem_2)?;
+
+            let elem_3 = Value::known(elem_1) + elem_2.value();
+            // Assign elem_3
+            let elem_3 = region.assign_advice(|| "elem_3", config.elem_3, offset, || elem_3)?;
 
             Ok((
                 elem_2,
@@ -86,30 +199,64 @@ impl Config {
         })
     }
 
-    fn assign<F: Field>(
+    /// Like [`FibonacciChip::init`], but with the seeds copied from rows
+    /// `elem_1_row` and `elem_2_row` of the instance column, so that the
+    /// verifier knows where the chain starts.
+    pub fn init_from_instance(
+        &self,
+        mut layouter: impl Layouter<F>,
+        elem_1_row: usize,
+        elem_2_row: usize,
+    ) -> Result<FibonacciCells<F>, Error> {
+        let config = self.config();
+
+        layouter.assign_region(|| "init public Fibonacci", |mut region| {
+            let offset = 0;
+
+            // Enable q_fib
+            config.q_fib.enable(&mut region, offset)?;
+
+            // Copy elem_1 and elem_2 from the instance column
+            let elem_1 = region.assign_advice_from_instance(|| "elem_1", config.instance, elem_1_row, config.elem_1, offset)?;
+            let elem_2 = region.assign_advice_from_instance(|| "elem_2", config.instance, elem_2_row, config.elem_2, offset)?;
+
+            let elem_3 = elem_1.value().copied() + elem_2.value();
+            // Assign elem_3
+            let elem_3 = region.assign_advice(|| "elem_3", config.elem_3, offset, || elem_3)?;
+
+            Ok((
+                elem_2,
+                elem_3
+            ))
+
+        })
+    }
+
+    /// Assigns one more step of the chain, copying the previous `elem_2` and
+    /// `elem_3` into the new row and returning the new `(elem_2, elem_3)`.
+    pub fn assign(
         &self,
         mut layouter: impl Layouter<F>,
         elem_2: AssignedCell<F, F>,
         elem_3: AssignedCell<F, F>,
-    ) -> Result<(
-        AssignedCell<F, F>, // elem_2
-        AssignedCell<F, F> // elem_3
-    ), Error> {
+    ) -> Result<FibonacciCells<F>, Error> {
+        let config = self.config();
+
         layouter.assign_region(|| "steady-state Fibonacci", |mut region| {
             let offset = 0;
 
             // Enable q_fib
-            self.q_fib.enable(&mut region, offset)?;
+            config.q_fib.enable(&mut region, offset)?;
 
             // Copy elem_1 (which is the previous elem_2)
-            let elem_1 = elem_2.copy_advice(|| "copy elem_2 into current elem_1", &mut region, self.elem_1, offset)?;
+            let elem_1 = elem_2.copy_advice(|| "copy elem_2 into current elem_1", &mut region, config.elem_1, offset)?;
 
             // Copy elem_2 (which is the previous elem_3)
-            let elem_2 = elem_3.copy_advice(|| "copy elem_3 into current elem_2", &mut region, self.elem_2, offset)?;
+            let elem_2 = elem_3.copy_advice(|| "copy elem_3 into current elem_2", &mut region, config.elem_2, offset)?;
 
             let elem_3 = elem_1.value_field().evaluate() + elem_2.value_field().evaluate();
             // Assign elem_3
-            let elem_3 = region.assign_advice(|| "elem_3", self.elem_3, offset, || elem_3)?;
+            let elem_3 = region.assign_advice(|| "elem_3", config.elem_3, offset, || elem_3)?;
 
             Ok((
                 elem_2,
@@ -118,6 +265,64 @@ impl Config {
 
         })
     }
+
+    /// Assigns the whole chain x_0, ..., x_n seeded with `elem_1` and `elem_2`
+    /// in a single region, returning the cells holding x_0, x_1 and x_n.
+    ///
+    /// The rows and copy constraints are the same as one [`FibonacciChip::init`]
+    /// followed by n - 2 calls to [`FibonacciChip::assign`], but the floor
+    /// planner only has to place one region instead of n - 1.
+    pub fn assign_chain(
+        &self,
+        mut layouter: impl Layouter<F>,
+        n: usize,
+        elem_1: Value<F>,
+        elem_2: Value<F>,
+    ) -> Result<ChainCells<F>, Error> {
+        let config = self.config();
+        assert!(n >= 2, "the chain has at least one Fibonacci step");
+
+        layouter.assign_region(|| "Fibonacci chain", |mut region| {
+            // Enable q_fib
+            config.q_fib.enable(&mut region, 0)?;
+
+            // Assign the seeds
+            let seed_1 = region.assign_advice(|| "elem_1", config.elem_1, 0, || elem_1)?;
+            let seed_2 = region.assign_advice(|| "elem_2", config.elem_2, 0, || elem_2)?;
+            let mut elem_2 = seed_2.clone();
+
+            let value = elem_1 + elem_2.value();
+            // Assign elem_3
+            let mut elem_3 = region.assign_advice(|| "elem_3", config.elem_3, 0, || value)?;
+
+            for offset in 1..n - 1 {
+                // Enable q_fib
+                config.q_fib.enable(&mut region, offset)?;
+
+                // Copy elem_1 (which is the previous elem_2)
+                let elem_1 = elem_2.copy_advice(|| "copy elem_2 into current elem_1", &mut region, config.elem_1, offset)?;
+
+                // Copy elem_2 (which is the previous elem_3)
+                elem_2 = elem_3.copy_advice(|| "copy elem_3 into current elem_2", &mut region, config.elem_2, offset)?;
+
+                let value = elem_1.value().copied() + elem_2.value();
+                // Assign elem_3
+                elem_3 = region.assign_advice(|| "elem_3", config.elem_3, offset, || value)?;
+            }
+
+            Ok((seed_1, seed_2, elem_3))
+        })
+    }
+
+    /// Constrains `cell` to equal the given `row` of the instance column.
+    pub fn expose_public(
+        &self,
+        mut layouter: impl Layouter<F>,
+        cell: &AssignedCell<F, F>,
+        row: usize,
+    ) -> Result<(), Error> {
+        layouter.constrain_instance(cell.cell(), self.config().instance, row)
+    }
 }
 
 #[cfg(test)]
@@ -142,13 +347,13 @@ mod tests {
 
 
     #[derive(Default)]
-    struct MyCircuit<F: Field> {
+    struct MyCircuit<F: FieldExt> {
         elem_1: Value<F>, // 1
         elem_2: Value<F>, // 1
     }
 
-    impl<F: Field> Circuit<F> for MyCircuit<F> {
-        type Config = Config;
+    impl<F: FieldExt> Circuit<F> for MyCircuit<F> {
+        type Config = FibonacciConfig;
 
         type FloorPlanner = SimpleFloorPlanner;
 
@@ -157,14 +362,19 @@ mod tests {
         }
 
         fn configure(meta: &mut ConstraintSystem<F>) -> Self::Config {
-            Self::Config::configure(meta)
+            FibonacciChip::configure(meta)
         }
 
         fn synthesize(&self, config: Self::Config, mut layouter: impl Layouter<F>) -> Result<(), Error> {
+            let chip = FibonacciChip::construct(config);
+
             // elem_2 = 1, elem_3 = 2
-            let (elem_2, elem_3) = config.init(layouter.namespace(|| "init"), self.elem_1, self.elem_2)?;
+            let (elem_2, elem_3) = chip.init(layouter.namespace(|| "init"), self.elem_1, self.elem_2)?;
             // 1 + 2 = 3
-            config.assign(layouter.namespace(|| "first assign after init"), elem_2, elem_3)?;
+            let (_, elem_3) = chip.assign(layouter.namespace(|| "first assign after init"), elem_2, elem_3)?;
+
+            // elem_3 = 3 is the public output
+            chip.expose_public(layouter.namespace(|| "out"), &elem_3, 0)?;
 
             Ok(())
         }
@@ -178,7 +388,172 @@ mod tests {
             elem_2: Value::known(Fp::one()),
         };
 
-        let prover = MockProver::run(3, &circuit, vec![]).unwrap();
+        let prover = MockProver::run(3, &circuit, vec![vec![Fp::from(3)]]).unwrap();
         prover.assert_satisfied();
     }
-}
\ No newline at end of file
+
+    #[test]
+    fn test_fib_wrong_output() {
+
+        let circuit = MyCircuit {
+            elem_1: Value::known(Fp::one()),
+            elem_2: Value::known(Fp::one()),
+        };
+
+        let prover = MockProver::run(3, &circuit, vec![vec![Fp::from(4)]]).unwrap();
+        assert!(prover.verify().is_err());
+    }
+
+    #[derive(Default)]
+    struct ChainCircuit {
+        n: usize,
+    }
+
+    impl Circuit<Fp> for ChainCircuit {
+        type Config = FibonacciConfig;
+
+        type FloorPlanner = SimpleFloorPlanner;
+
+        fn without_witnesses(&self) -> Self {
+            Self { n: self.n }
+        }
+
+        fn configure(meta: &mut ConstraintSystem<Fp>) -> Self::Config {
+            FibonacciChip::configure(meta)
+        }
+
+        fn synthesize(&self, config: Self::Config, mut layouter: impl Layouter<Fp>) -> Result<(), Error> {
+            let chip = FibonacciChip::construct(config);
+
+            let (_, _, out) = chip.assign_chain(
+                layouter.namespace(|| "chain"),
+                self.n,
+                Value::known(Fp::one()),
+                Value::known(Fp::one()),
+            )?;
+            chip.expose_public(layouter.namespace(|| "out"), &out, 0)
+        }
+    }
+
+    #[test]
+    fn test_assign_chain() {
+        // 1, 1, 2, 3, 5, 8, 13, 21, 34, 55
+        let prover = MockProver::run(5, &ChainCircuit { n: 9 }, vec![vec![Fp::from(55)]]).unwrap();
+        prover.assert_satisfied();
+
+        let prover = MockProver::run(5, &ChainCircuit { n: 2 }, vec![vec![Fp::from(2)]]).unwrap();
+        prover.assert_satisfied();
+
+        let prover = MockProver::run(5, &ChainCircuit { n: 9 }, vec![vec![Fp::from(56)]]).unwrap();
+        assert!(prover.verify().is_err());
+    }
+
+    /// Proves the n-th term of the chain with constant seeds, Lucas seeds if
+    /// `lucas` is set and standard ones otherwise.
+    #[derive(Default)]
+    struct ConstantSeedsCircuit {
+        n: usize,
+        lucas: bool,
+    }
+
+    impl Circuit<Fp> for ConstantSeedsCircuit {
+        type Config = FibonacciConfig;
+
+        type FloorPlanner = SimpleFloorPlanner;
+
+        fn without_witnesses(&self) -> Self {
+            Self { n: self.n, lucas: self.lucas }
+        }
+
+        fn configure(meta: &mut ConstraintSystem<Fp>) -> Self::Config {
+            FibonacciChip::configure(meta)
+        }
+
+        fn synthesize(&self, config: Self::Config, mut layouter: impl Layouter<Fp>) -> Result<(), Error> {
+            let chip = FibonacciChip::construct(config);
+
+            let (mut elem_2, mut elem_3) = if self.lucas {
+                chip.init_lucas(layouter.namespace(|| "init"))?
+            } else {
+                chip.init_standard(layouter.namespace(|| "init"))?
+            };
+            for i in 3..=self.n {
+                (elem_2, elem_3) = chip.assign(layouter.namespace(|| format!("x_{}", i)), elem_2, elem_3)?;
+            }
+
+            chip.expose_public(layouter.namespace(|| "out"), &elem_3, 0)
+        }
+    }
+
+    #[test]
+    fn test_init_standard() {
+        // F(10) = 55
+        let circuit = ConstantSeedsCircuit { n: 10, lucas: false };
+        let prover = MockProver::run(5, &circuit, vec![vec![Fp::from(55)]]).unwrap();
+        prover.assert_satisfied();
+
+        // 89 is the 10th term of the chain seeded with 1, 1, but not F(10).
+        let prover = MockProver::run(5, &circuit, vec![vec![Fp::from(89)]]).unwrap();
+        assert!(prover.verify().is_err());
+    }
+
+    #[test]
+    fn test_init_lucas() {
+        // L(10) = 123
+        let circuit = ConstantSeedsCircuit { n: 10, lucas: true };
+        let prover = MockProver::run(5, &circuit, vec![vec![Fp::from(123)]]).unwrap();
+        prover.assert_satisfied();
+
+        let prover = MockProver::run(5, &circuit, vec![vec![Fp::from(55)]]).unwrap();
+        assert!(prover.verify().is_err());
+    }
+
+    /// Proves the n-th term of the chain seeded with rows 1 and 2 of the
+    /// instance column, exposing it in row 0.
+    #[derive(Default)]
+    struct PublicSeedsCircuit {
+        n: usize,
+    }
+
+    impl Circuit<Fp> for PublicSeedsCircuit {
+        type Config = FibonacciConfig;
+
+        type FloorPlanner = SimpleFloorPlanner;
+
+        fn without_witnesses(&self) -> Self {
+            Self { n: self.n }
+        }
+
+        fn configure(meta: &mut ConstraintSystem<Fp>) -> Self::Config {
+            FibonacciChip::configure(meta)
+        }
+
+        fn synthesize(&self, config: Self::Config, mut layouter: impl Layouter<Fp>) -> Result<(), Error> {
+            let chip = FibonacciChip::construct(config);
+
+            let (mut elem_2, mut elem_3) = chip.init_from_instance(layouter.namespace(|| "init"), 1, 2)?;
+            for i in 3..=self.n {
+                (elem_2, elem_3) = chip.assign(layouter.namespace(|| format!("x_{}", i)), elem_2, elem_3)?;
+            }
+
+            chip.expose_public(layouter.namespace(|| "out"), &elem_3, 0)
+        }
+    }
+
+    #[test]
+    fn test_init_from_instance() {
+        let circuit = PublicSeedsCircuit { n: 10 };
+
+        // L(10) = 123
+        let prover = MockProver::run(5, &circuit, vec![vec![Fp::from(123), Fp::from(2), Fp::from(1)]]).unwrap();
+        prover.assert_satisfied();
+
+        // F(10) = 55
+        let prover = MockProver::run(5, &circuit, vec![vec![Fp::from(55), Fp::from(0), Fp::from(1)]]).unwrap();
+        prover.assert_satisfied();
+
+        // The output does not follow from the public seeds.
+        let prover = MockProver::run(5, &circuit, vec![vec![Fp::from(55), Fp::from(2), Fp::from(1)]]).unwrap();
+        assert!(prover.verify().is_err());
+    }
+}
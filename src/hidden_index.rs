@@ -0,0 +1,313 @@
+/*
+
+    Proves that a public x is F(n) for some private 1 <= n <= N, where the
+    bound N is fixed by the layout. For N = 5 and n = 4:
+
+    |  a   |  b   | is_selected | count | acc | q_first | q_step
+    ------------------------------------------------------------
+    |  0   |  1   |      0      |   0   |  0  |    1    |   1
+    |  1   |  1   |      0      |   0   |  0  |    0    |   1
+    |  1   |  2   |      0      |   0   |  0  |    0    |   1
+    |  2   |  3   |      1      |   1   |  3  |    0    |   1
+    |  3   |  5   |      0      |   1   |  3  |    0    |   0
+
+    Row i holds (F(i), F(i + 1)), starting from the constants (0, 1). count
+    and acc are running sums of is_selected and is_selected * b:
+
+    q_first * is_selected * (1 - is_selected) = 0
+    q_first * (count - is_selected) = 0
+    q_first * (acc - is_selected * b) = 0
+    q_step * is_selected_next * (1 - is_selected_next) = 0
+    q_step * (a_next - b) = 0
+    q_step * (b_next - a - b) = 0
+    q_step * (count_next - count - is_selected_next) = 0
+    q_step * (acc_next - acc - is_selected_next * b_next) = 0
+
+    Between them the gates cover every row, and the last count is constrained
+    to 1, so exactly one row is selected. The last acc is then its b, and is
+    exposed in row 0 of the instance column.
+
+*/
+
+use std::marker::PhantomData;
+
+use halo2_proofs::arithmetic::FieldExt;
+use halo2_proofs::circuit::{AssignedCell, Chip, Layouter, SimpleFloorPlanner, Value};
+use halo2_proofs::plonk::*;
+use halo2_proofs::poly::Rotation;
+
+use crate::circuit::min_k;
+
+/// Columns and selectors used by the `hidden index` gates.
+#[derive(Clone, Debug, Copy)]
+pub struct HiddenIndexConfig {
+    pub a: Column<Advice>,
+    pub b: Column<Advice>,
+    pub is_selected: Column<Advice>,
+    pub count: Column<Advice>,
+    pub acc: Column<Advice>,
+    pub constants: Column<Fixed>,
+    pub q_first: Selector,
+    pub q_step: Selector,
+    pub instance: Column<Instance>,
+}
+
+/// A chip selecting one term of the chain F(1), ..., F(N) without revealing
+/// which.
+#[derive(Clone, Debug)]
+pub struct HiddenIndexChip<F: FieldExt> {
+    config: HiddenIndexConfig,
+    _marker: PhantomData<F>,
+}
+
+impl<F: FieldExt> Chip<F> for HiddenIndexChip<F> {
+    type Config = HiddenIndexConfig;
+    type Loaded = ();
+
+    fn config(&self) -> &Self::Config {
+        &self.config
+    }
+
+    fn loaded(&self) -> &Self::Loaded {
+        &()
+    }
+}
+
+impl<F: FieldExt> HiddenIndexChip<F> {
+    /// Constructs a chip from a config returned by
+    /// [`HiddenIndexChip::configure`].
+    pub fn construct(config: HiddenIndexConfig) -> Self {
+        Self {
+            config,
+            _marker: PhantomData,
+        }
+    }
+
+    /// Allocates five advice columns, a constants column, two selectors and an
+    /// instance column, and creates the `hidden index` gates over them.
+    pub fn configure(cs: &mut ConstraintSystem<F>) -> HiddenIndexConfig {
+        let a = cs.advice_column();
+        cs.enable_equality(a);
+        let b = cs.advice_column();
+        cs.enable_equality(b);
+        let is_selected = cs.advice_column();
+        let count = cs.advice_column();
+        cs.enable_equality(count);
+        let acc = cs.advice_column();
+        cs.enable_equality(acc);
+        let constants = cs.fixed_column();
+        cs.enable_constant(constants);
+        let q_first = cs.selector();
+        let q_step = cs.selector();
+        let instance = cs.instance_column();
+        cs.enable_equality(instance);
+
+        cs.create_gate("hidden index first row", |virtual_cells| {
+            let q_first = virtual_cells.query_selector(q_first);
+            let b = virtual_cells.query_advice(b, Rotation::cur());
+            let is_selected = virtual_cells.query_advice(is_selected, Rotation::cur());
+            let count = virtual_cells.query_advice(count, Rotation::cur());
+            let acc = virtual_cells.query_advice(acc, Rotation::cur());
+            let one = Expression::Constant(F::one());
+
+            Constraints::with_selector(
+                q_first,
+                [
+                    ("is_selected is boolean", is_selected.clone() * (one - is_selected.clone())),
+                    ("count", count - is_selected.clone()),
+                    ("acc", acc - is_selected * b),
+                ],
+            )
+        });
+
+        cs.create_gate("hidden index step", |virtual_cells| {
+            let q_step = virtual_cells.query_selector(q_step);
+            let a_cur = virtual_cells.query_advice(a, Rotation::cur());
+            let b_cur = virtual_cells.query_advice(b, Rotation::cur());
+            let count_cur = virtual_cells.query_advice(count, Rotation::cur());
+            let acc_cur = virtual_cells.query_advice(acc, Rotation::cur());
+            let a_next = virtual_cells.query_advice(a, Rotation::next());
+            let b_next = virtual_cells.query_advice(b, Rotation::next());
+            let is_selected_next = virtual_cells.query_advice(is_selected, Rotation::next());
+            let count_next = virtual_cells.query_advice(count, Rotation::next());
+            let acc_next = virtual_cells.query_advice(acc, Rotation::next());
+            let one = Expression::Constant(F::one());
+
+            Constraints::with_selector(
+                q_step,
+                [
+                    ("is_selected_next is boolean", is_selected_next.clone() * (one - is_selected_next.clone())),
+                    ("a_next", a_next - b_cur.clone()),
+                    ("b_next", b_next.clone() - a_cur - b_cur),
+                    ("count_next", count_next - count_cur - is_selected_next.clone()),
+                    ("acc_next", acc_next - acc_cur - is_selected_next * b_next),
+                ],
+            )
+        });
+
+        HiddenIndexConfig { a, b, is_selected, count, acc, constants, q_first, q_step, instance }
+    }
+
+    /// Lays out F(1), ..., F(bound) and selects F(index), returning the cell
+    /// holding the selected term.
+    ///
+    /// Returns [`Error::Synthesis`] if `index` is known and not in
+    /// `1..=bound`.
+    pub fn assign(
+        &self,
+        mut layouter: impl Layouter<F>,
+        bound: usize,
+        index: Value<usize>,
+    ) -> Result<AssignedCell<F, F>, Error> {
+        let config = self.config();
+        assert!(bound >= 2, "the chain has at least one Fibonacci step");
+        index.error_if_known_and(|index| *index == 0 || *index > bound)?;
+
+        layouter.assign_region(|| "hidden index", |mut region| {
+            // The first row starts from (F(0), F(1))
+            config.q_first.enable(&mut region, 0)?;
+            let mut a = region.assign_advice_from_constant(|| "F(0)", config.a, 0, F::zero())?;
+            let mut b = region.assign_advice_from_constant(|| "F(1)", config.b, 0, F::one())?;
+
+            let selected = |offset: usize| index.map(|index| F::from((index == offset + 1) as u64));
+            let is_selected = selected(0);
+            region.assign_advice(|| "is_selected", config.is_selected, 0, || is_selected)?;
+            let mut count = region.assign_advice(|| "count", config.count, 0, || is_selected)?;
+            let mut acc = region.assign_advice(|| "acc", config.acc, 0, || is_selected * b.value())?;
+
+            for offset in 1..bound {
+                // Enable q_step on the previous row
+                config.q_step.enable(&mut region, offset - 1)?;
+
+                let b_next = a.value().copied() + b.value();
+                a = region.assign_advice(|| format!("F({})", offset), config.a, offset, || b.value().copied())?;
+                b = region.assign_advice(|| format!("F({})", offset + 1), config.b, offset, || b_next)?;
+
+                let is_selected = selected(offset);
+                region.assign_advice(|| "is_selected", config.is_selected, offset, || is_selected)?;
+                let count_next = count.value().copied() + is_selected;
+                count = region.assign_advice(|| "count", config.count, offset, || count_next)?;
+                let acc_next = acc.value().copied() + is_selected * b.value();
+                acc = region.assign_advice(|| "acc", config.acc, offset, || acc_next)?;
+            }
+
+            // Exactly one row is selected
+            region.constrain_constant(count.cell(), F::one())?;
+
+            Ok(acc)
+        })
+    }
+
+    /// Constrains `cell` to equal the given `row` of the instance column.
+    pub fn expose_public(
+        &self,
+        mut layouter: impl Layouter<F>,
+        cell: &AssignedCell<F, F>,
+        row: usize,
+    ) -> Result<(), Error> {
+        layouter.constrain_instance(cell.cell(), self.config().instance, row)
+    }
+}
+
+/// A circuit proving that row 0 of the instance column is F(n) for a private
+/// n in `1..=bound`.
+#[derive(Clone, Debug)]
+pub struct HiddenIndexCircuit<F: FieldExt> {
+    bound: usize,
+    index: Value<usize>,
+    _marker: PhantomData<F>,
+}
+
+impl<F: FieldExt> Default for HiddenIndexCircuit<F> {
+    fn default() -> Self {
+        Self {
+            bound: 0,
+            index: Value::unknown(),
+            _marker: PhantomData,
+        }
+    }
+}
+
+impl<F: FieldExt> HiddenIndexCircuit<F> {
+    /// Creates a circuit hiding `index` among `1..=bound`, which must have
+    /// `bound >= 2`.
+    pub fn new(bound: usize, index: Value<usize>) -> Self {
+        assert!(bound >= 2, "the circuit has at least one Fibonacci step");
+        Self {
+            bound,
+            index,
+            _marker: PhantomData,
+        }
+    }
+
+    /// The smallest `k` for which the circuit fits.
+    pub fn k(&self) -> u32 {
+        let mut cs = ConstraintSystem::default();
+        Self::configure(&mut cs);
+        min_k(self.bound, &cs)
+    }
+}
+
+impl<F: FieldExt> Circuit<F> for HiddenIndexCircuit<F> {
+    type Config = HiddenIndexConfig;
+
+    type FloorPlanner = SimpleFloorPlanner;
+
+    fn without_witnesses(&self) -> Self {
+        Self { bound: self.bound, ..Self::default() }
+    }
+
+    fn configure(meta: &mut ConstraintSystem<F>) -> Self::Config {
+        HiddenIndexChip::configure(meta)
+    }
+
+    fn synthesize(&self, config: Self::Config, mut layouter: impl Layouter<F>) -> Result<(), Error> {
+        let chip = HiddenIndexChip::construct(config);
+
+        let selected = chip.assign(layouter.namespace(|| "chain"), self.bound, self.index)?;
+        chip.expose_public(layouter.namespace(|| "out"), &selected, 0)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use halo2_proofs::{dev::MockProver, pasta::Fp};
+
+    use super::*;
+    use crate::circuit::nth_term;
+
+    const BOUND: usize = 20;
+
+    #[test]
+    fn test_every_index() {
+        for index in 1..=BOUND {
+            let circuit = HiddenIndexCircuit::new(BOUND, Value::known(index));
+            let output = nth_term(index, Fp::zero(), Fp::one());
+
+            let prover = MockProver::run(circuit.k(), &circuit, vec![vec![output]]).unwrap();
+            prover.assert_satisfied();
+        }
+    }
+
+    #[test]
+    fn test_not_fibonacci() {
+        let circuit = HiddenIndexCircuit::new(BOUND, Value::known(6));
+
+        // F(6) = 8, and 7 is not a Fibonacci number.
+        let prover = MockProver::run(circuit.k(), &circuit, vec![vec![Fp::from(7)]]).unwrap();
+        assert!(prover.verify().is_err());
+
+        // F(21) is beyond the bound.
+        let output = nth_term(BOUND + 1, Fp::zero(), Fp::one());
+        let prover = MockProver::run(circuit.k(), &circuit, vec![vec![output]]).unwrap();
+        assert!(prover.verify().is_err());
+    }
+
+    #[test]
+    fn test_index_out_of_range() {
+        for index in [0, BOUND + 1] {
+            let circuit = HiddenIndexCircuit::<Fp>::new(BOUND, Value::known(index));
+            assert!(MockProver::run(circuit.k(), &circuit, vec![vec![Fp::zero()]]).is_err());
+        }
+    }
+}
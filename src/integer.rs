@@ -0,0 +1,216 @@
+/*
+
+    The `fibonacci` gate holds modulo p, so past F(n) >= p the chain proves
+    F(n) mod p. Here every elem_3 is also range-checked below 2^B:
+
+    | elem_1 | elem_2 | elem_3 | q_fib |     | z (range check of elem_3)
+    ------------------------------------     --------------------------
+    |    0   |    1   |    1   |   1   |     | elem_3, z_1, ..., z_{B/8} = 0
+    |    1   |    1   |    2   |   1   |     | ...
+    |   ...  |   ...  |   ...  |  ...  |
+
+    Starting from the constant seeds 0 and 1, if elem_1 and elem_2 are below
+    2^B then elem_1 + elem_2 < 2^(B + 1) <= p, so elem_3 is their integer sum.
+    By induction every cell of the chain is the integer F(i).
+
+*/
+
+use std::marker::PhantomData;
+
+use halo2_proofs::arithmetic::FieldExt;
+use halo2_proofs::circuit::{Chip, Layouter, SimpleFloorPlanner};
+use halo2_proofs::plonk::*;
+
+use crate::circuit::min_k;
+use crate::fibonacci::{FibonacciCells, FibonacciChip, FibonacciConfig};
+use crate::range_check::{fits_in_bits, RangeCheckChip, RangeCheckConfig, K};
+
+/// The configs of the Fibonacci chain and of its range checks.
+#[derive(Clone, Debug, Copy)]
+pub struct IntegerConfig {
+    pub fibonacci: FibonacciConfig,
+    pub range_check: RangeCheckConfig,
+}
+
+/// A chip proving integer Fibonacci numbers below 2^`num_bits`.
+#[derive(Clone, Debug)]
+pub struct IntegerChip<F: FieldExt> {
+    config: IntegerConfig,
+    num_bits: usize,
+    _marker: PhantomData<F>,
+}
+
+impl<F: FieldExt> Chip<F> for IntegerChip<F> {
+    type Config = IntegerConfig;
+    type Loaded = ();
+
+    fn config(&self) -> &Self::Config {
+        &self.config
+    }
+
+    fn loaded(&self) -> &Self::Loaded {
+        &()
+    }
+}
+
+impl<F: FieldExt> IntegerChip<F> {
+    /// Constructs a chip from a config returned by [`IntegerChip::configure`],
+    /// bounding every term by 2^`num_bits`. `num_bits` must be a multiple of
+    /// K small enough that the sum of two terms does not wrap around.
+    pub fn construct(config: IntegerConfig, num_bits: usize) -> Self {
+        assert_eq!(num_bits % K, 0, "num_bits is a multiple of K");
+        assert!(num_bits + 1 < F::NUM_BITS as usize, "the sum of two terms fits in the field");
+        Self {
+            config,
+            num_bits,
+            _marker: PhantomData,
+        }
+    }
+
+    /// Configures a [`FibonacciChip`] and a [`RangeCheckChip`].
+    pub fn configure(cs: &mut ConstraintSystem<F>) -> IntegerConfig {
+        IntegerConfig {
+            fibonacci: FibonacciChip::configure(cs),
+            range_check: RangeCheckChip::configure(cs),
+        }
+    }
+
+    /// Loads the range check table. Must be called once per circuit.
+    pub fn load_table(&self, layouter: impl Layouter<F>) -> Result<(), Error> {
+        RangeCheckChip::construct(self.config().range_check).load_table(layouter)
+    }
+
+    /// Assigns the first row of the chain from the constant seeds 0 and 1, as
+    /// [`FibonacciChip::init_standard`] does.
+    pub fn init_standard(&self, mut layouter: impl Layouter<F>) -> Result<FibonacciCells<F>, Error> {
+        let fibonacci = FibonacciChip::construct(self.config().fibonacci);
+        let range_check = RangeCheckChip::construct(self.config().range_check);
+
+        let (elem_2, elem_3) = fibonacci.init_standard(layouter.namespace(|| "init"))?;
+        range_check.copy_check(layouter.namespace(|| "range check elem_3"), &elem_3, self.num_bits)?;
+        Ok((elem_2, elem_3))
+    }
+
+    /// Assigns one more step of the chain from the cells returned by the
+    /// previous step, as [`FibonacciChip::assign`] does, and range-checks the
+    /// new `elem_3`.
+    ///
+    /// Returns [`Error::Synthesis`] if the new term is known and not below
+    /// 2^`num_bits`.
+    pub fn assign(
+        &self,
+        mut layouter: impl Layouter<F>,
+        prev: &FibonacciCells<F>,
+    ) -> Result<FibonacciCells<F>, Error> {
+        let fibonacci = FibonacciChip::construct(self.config().fibonacci);
+        let range_check = RangeCheckChip::construct(self.config().range_check);
+
+        let (elem_2, elem_3) = prev;
+        let sum = elem_2.value().copied() + elem_3.value();
+        sum.error_if_known_and(|sum| !fits_in_bits(sum, self.num_bits))?;
+
+        let (elem_2, elem_3) = fibonacci.assign(layouter.namespace(|| "step"), elem_2.clone(), elem_3.clone())?;
+        range_check.copy_check(layouter.namespace(|| "range check elem_3"), &elem_3, self.num_bits)?;
+        Ok((elem_2, elem_3))
+    }
+}
+
+/// A circuit proving the integer F(n), exposing it in row 0 of the instance
+/// column.
+///
+/// Synthesis fails if F(n) is not below 2^`num_bits`.
+#[derive(Clone, Debug)]
+pub struct IntegerCircuit<F: FieldExt> {
+    n: usize,
+    num_bits: usize,
+    _marker: PhantomData<F>,
+}
+
+impl<F: FieldExt> IntegerCircuit<F> {
+    /// Creates a circuit for F(n) bounded by 2^`num_bits`, which must have
+    /// `n >= 2`.
+    pub fn new(n: usize, num_bits: usize) -> Self {
+        assert!(n >= 2, "the circuit has at least one Fibonacci step");
+        Self {
+            n,
+            num_bits,
+            _marker: PhantomData,
+        }
+    }
+
+    /// The smallest `k` for which the circuit fits.
+    pub fn k(&self) -> u32 {
+        let mut cs = ConstraintSystem::default();
+        Self::configure(&mut cs);
+        // Each step takes one row of the chain and num_bits / K + 1 rows of
+        // range check.
+        let rows = (self.n - 1) * (self.num_bits / K + 2);
+        min_k(std::cmp::max(rows, 1 << K), &cs)
+    }
+}
+
+impl<F: FieldExt> Circuit<F> for IntegerCircuit<F> {
+    type Config = IntegerConfig;
+
+    type FloorPlanner = SimpleFloorPlanner;
+
+    fn without_witnesses(&self) -> Self {
+        self.clone()
+    }
+
+    fn configure(meta: &mut ConstraintSystem<F>) -> Self::Config {
+        IntegerChip::configure(meta)
+    }
+
+    fn synthesize(&self, config: Self::Config, mut layouter: impl Layouter<F>) -> Result<(), Error> {
+        let chip = IntegerChip::construct(config, self.num_bits);
+        chip.load_table(layouter.namespace(|| "table"))?;
+
+        let mut cells = chip.init_standard(layouter.namespace(|| "init"))?;
+        for i in 3..=self.n {
+            cells = chip.assign(layouter.namespace(|| format!("x_{}", i)), &cells)?;
+        }
+
+        let fibonacci = FibonacciChip::construct(config.fibonacci);
+        fibonacci.expose_public(layouter.namespace(|| "out"), &cells.1, 0)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use halo2_proofs::{dev::MockProver, pasta::Fp};
+
+    use super::*;
+    use crate::circuit::nth_term;
+
+    #[test]
+    fn test_integer() {
+        // F(93) is the largest Fibonacci number below 2^64.
+        for n in [2, 10, 93] {
+            let circuit = IntegerCircuit::new(n, 64);
+            let output = nth_term(n, Fp::zero(), Fp::one());
+
+            let prover = MockProver::run(circuit.k(), &circuit, vec![vec![output]]).unwrap();
+            prover.assert_satisfied();
+        }
+    }
+
+    #[test]
+    fn test_too_large() {
+        let circuit = IntegerCircuit::<Fp>::new(94, 64);
+        let output = nth_term(94, Fp::zero(), Fp::one());
+        assert!(MockProver::run(circuit.k(), &circuit, vec![vec![output]]).is_err());
+
+        // F(14) = 377 does not fit in a byte.
+        let circuit = IntegerCircuit::<Fp>::new(14, 8);
+        assert!(MockProver::run(circuit.k(), &circuit, vec![vec![Fp::from(377)]]).is_err());
+    }
+
+    #[test]
+    fn test_wrong_output() {
+        let circuit = IntegerCircuit::new(10, 64);
+
+        let prover = MockProver::run(circuit.k(), &circuit, vec![vec![Fp::from(56)]]).unwrap();
+        assert!(prover.verify().is_err());
+    }
+}
@@ -0,0 +1,307 @@
+/*
+
+    A non-negative integer x is a Fibonacci number if and only if 5x^2 + 4 or
+    5x^2 - 4 is a perfect square. For x = 8, 5 * 64 - 4 = 316 is not a square
+    but 5 * 64 + 4 = 324 = 18^2:
+
+    |  x  |  s  | sign | q_square
+    -----------------------------
+    |  8  | 18  |   1  |    1
+
+    q_square * sign * (1 - sign) = 0
+    q_square * (5 * x^2 + 4 * (2 * sign - 1) - s^2) = 0
+
+    x and s are range-checked to X_BITS and S_BITS bits, so neither side of
+    the equation wraps around the field and it holds over the integers.
+
+*/
+
+use std::marker::PhantomData;
+
+use halo2_proofs::arithmetic::FieldExt;
+use halo2_proofs::circuit::{AssignedCell, Chip, Layouter, Value};
+use halo2_proofs::plonk::*;
+use halo2_proofs::poly::Rotation;
+
+use crate::range_check::{fits_in_bits, RangeCheckChip, RangeCheckConfig};
+
+/// The number of bits of the values the gadget accepts.
+pub const X_BITS: usize = 64;
+
+/// The number of bits of the square root, which is below sqrt(5) * 2^X_BITS.
+pub const S_BITS: usize = X_BITS + 8;
+
+/// Returns the square root `s` and sign of the perfect square
+/// `5x^2 + 4` (sign 1) or `5x^2 - 4` (sign 0), or `None` if `x` is not a
+/// Fibonacci number below 2^X_BITS.
+pub fn square_witness<F: FieldExt>(x: F) -> Option<(F, bool)> {
+    if !fits_in_bits(&x, X_BITS) {
+        return None;
+    }
+
+    let five_x_squared = x.square() * F::from(5);
+    [true, false].into_iter().find_map(|sign| {
+        let square = if sign { five_x_squared + F::from(4) } else { five_x_squared - F::from(4) };
+        let s: Option<F> = square.sqrt().into();
+        // The field has two roots; only the small one is the integer root.
+        s.and_then(|s| [s, -s].into_iter().find(|s| fits_in_bits(s, S_BITS)))
+            .map(|s| (s, sign))
+    })
+}
+
+/// Columns and selector used by the `is fibonacci` gate.
+#[derive(Clone, Debug, Copy)]
+pub struct IsFibonacciConfig {
+    pub x: Column<Advice>,
+    pub s: Column<Advice>,
+    pub sign: Column<Advice>,
+    pub q_square: Selector,
+    pub range_check: RangeCheckConfig,
+}
+
+/// A chip proving that a value is a Fibonacci number without computing the
+/// sequence.
+#[derive(Clone, Debug)]
+pub struct IsFibonacciChip<F: FieldExt> {
+    config: IsFibonacciConfig,
+    _marker: PhantomData<F>,
+}
+
+impl<F: FieldExt> Chip<F> for IsFibonacciChip<F> {
+    type Config = IsFibonacciConfig;
+    type Loaded = ();
+
+    fn config(&self) -> &Self::Config {
+        &self.config
+    }
+
+    fn loaded(&self) -> &Self::Loaded {
+        &()
+    }
+}
+
+impl<F: FieldExt> IsFibonacciChip<F> {
+    /// Constructs a chip from a config returned by
+    /// [`IsFibonacciChip::configure`].
+    pub fn construct(config: IsFibonacciConfig) -> Self {
+        Self {
+            config,
+            _marker: PhantomData,
+        }
+    }
+
+    /// Allocates three advice columns and a selector, and creates the
+    /// `is fibonacci` gate over them. Range checks use `range_check`, whose
+    /// table the caller loads.
+    pub fn configure(cs: &mut ConstraintSystem<F>, range_check: RangeCheckConfig) -> IsFibonacciConfig {
+        let x = cs.advice_column();
+        cs.enable_equality(x);
+        let s = cs.advice_column();
+        cs.enable_equality(s);
+        let sign = cs.advice_column();
+        let q_square = cs.selector();
+
+        cs.create_gate("is fibonacci", |virtual_cells| {
+            let q_square = virtual_cells.query_selector(q_square);
+            let x = virtual_cells.query_advice(x, Rotation::cur());
+            let s = virtual_cells.query_advice(s, Rotation::cur());
+            let sign = virtual_cells.query_advice(sign, Rotation::cur());
+
+            let one = Expression::Constant(F::one());
+            let five = Expression::Constant(F::from(5));
+            let four = Expression::Constant(F::from(4));
+            let two = Expression::Constant(F::from(2));
+
+            Constraints::with_selector(
+                q_square,
+                [
+                    ("sign is boolean", sign.clone() * (one.clone() - sign.clone())),
+                    ("5x^2 ± 4 = s^2", five * x.clone() * x + four * (two * sign - one) - s.clone() * s),
+                ],
+            )
+        });
+
+        IsFibonacciConfig { x, s, sign, q_square, range_check }
+    }
+
+    /// Witnesses `x` and proves that it is a Fibonacci number below
+    /// 2^X_BITS, returning the cell holding it.
+    ///
+    /// Returns [`Error::Synthesis`] if `x` is known and not such a number.
+    pub fn assign(&self, layouter: impl Layouter<F>, x: Value<F>) -> Result<AssignedCell<F, F>, Error> {
+        let witness = x.map(square_witness);
+        witness.error_if_known_and(Option::is_none)?;
+        let (s, sign) = witness.map(Option::unwrap).unzip();
+
+        self.assign_square(layouter, x, s, sign.map(F::from))
+    }
+
+    /// Assigns `x`, the square root `s` and `sign` in one row and range-checks
+    /// `x` and `s`, returning the cell holding `x`.
+    fn assign_square(
+        &self,
+        mut layouter: impl Layouter<F>,
+        x: Value<F>,
+        s: Value<F>,
+        sign: Value<F>,
+    ) -> Result<AssignedCell<F, F>, Error> {
+        let config = self.config();
+        let range_check = RangeCheckChip::construct(config.range_check);
+
+        let (x, s) = layouter.assign_region(|| "is fibonacci", |mut region| {
+            let offset = 0;
+
+            // Enable q_square
+            config.q_square.enable(&mut region, offset)?;
+
+            let x = region.assign_advice(|| "x", config.x, offset, || x)?;
+            let s = region.assign_advice(|| "s", config.s, offset, || s)?;
+            region.assign_advice(|| "sign", config.sign, offset, || sign)?;
+
+            Ok((x, s))
+        })?;
+
+        range_check.copy_check(layouter.namespace(|| "x"), &x, X_BITS)?;
+        range_check.copy_check(layouter.namespace(|| "s"), &s, S_BITS)?;
+
+        Ok(x)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use halo2_proofs::{circuit::SimpleFloorPlanner, dev::MockProver, pasta::group::ff::Field, pasta::Fp};
+
+    use super::*;
+    use crate::circuit::nth_term;
+
+    #[derive(Clone, Default)]
+    struct IsFibonacciCircuit {
+        x: Value<Fp>,
+    }
+
+    impl Circuit<Fp> for IsFibonacciCircuit {
+        type Config = (IsFibonacciConfig, Column<Instance>);
+
+        type FloorPlanner = SimpleFloorPlanner;
+
+        fn without_witnesses(&self) -> Self {
+            Self::default()
+        }
+
+        fn configure(meta: &mut ConstraintSystem<Fp>) -> Self::Config {
+            let range_check = RangeCheckChip::configure(meta);
+            let instance = meta.instance_column();
+            meta.enable_equality(instance);
+            (IsFibonacciChip::configure(meta, range_check), instance)
+        }
+
+        fn synthesize(&self, config: Self::Config, mut layouter: impl Layouter<Fp>) -> Result<(), Error> {
+            let (config, instance) = config;
+            RangeCheckChip::construct(config.range_check).load_table(layouter.namespace(|| "table"))?;
+            let chip = IsFibonacciChip::construct(config);
+
+            let x = chip.assign(layouter.namespace(|| "x"), self.x)?;
+            layouter.constrain_instance(x.cell(), instance, 0)
+        }
+    }
+
+    /// Assigns `(x, s, sign)` without computing `s` and `sign` from `x`, as a
+    /// cheating prover would.
+    #[derive(Clone, Default)]
+    struct ForgedCircuit {
+        x: Value<Fp>,
+        s: Value<Fp>,
+        sign: Value<Fp>,
+    }
+
+    impl Circuit<Fp> for ForgedCircuit {
+        type Config = (IsFibonacciConfig, Column<Instance>);
+
+        type FloorPlanner = SimpleFloorPlanner;
+
+        fn without_witnesses(&self) -> Self {
+            Self::default()
+        }
+
+        fn configure(meta: &mut ConstraintSystem<Fp>) -> Self::Config {
+            IsFibonacciCircuit::configure(meta)
+        }
+
+        fn synthesize(&self, config: Self::Config, mut layouter: impl Layouter<Fp>) -> Result<(), Error> {
+            let (config, instance) = config;
+            RangeCheckChip::construct(config.range_check).load_table(layouter.namespace(|| "table"))?;
+            let chip = IsFibonacciChip::construct(config);
+
+            let x = chip.assign_square(layouter.namespace(|| "x"), self.x, self.s, self.sign)?;
+            layouter.constrain_instance(x.cell(), instance, 0)
+        }
+    }
+
+    fn verify_forged(x: Fp, s: Fp, sign: Fp) -> bool {
+        let circuit = ForgedCircuit { x: Value::known(x), s: Value::known(s), sign: Value::known(sign) };
+        MockProver::run(9, &circuit, vec![vec![x]]).unwrap().verify().is_ok()
+    }
+
+    #[test]
+    fn test_square_witness() {
+        let mut fibonacci = vec![0u64, 1];
+        while let Some(next) = fibonacci[fibonacci.len() - 2].checked_add(fibonacci[fibonacci.len() - 1]) {
+            fibonacci.push(next);
+        }
+        assert_eq!(Fp::from(fibonacci[93]), nth_term(93, Fp::zero(), Fp::one()));
+
+        for x in 0..2000u64 {
+            assert_eq!(square_witness(Fp::from(x)).is_some(), fibonacci.contains(&x), "x = {}", x);
+        }
+        for x in fibonacci {
+            assert!(square_witness(Fp::from(x)).is_some(), "x = {}", x);
+        }
+        assert!(square_witness(Fp::from(u64::MAX)).is_none());
+    }
+
+    #[test]
+    fn test_is_fibonacci() {
+        // F(93) is the largest Fibonacci number below 2^64.
+        for x in [0, 1, 2, 8, 144, 12200160415121876738] {
+            let circuit = IsFibonacciCircuit { x: Value::known(Fp::from(x)) };
+            let prover = MockProver::run(9, &circuit, vec![vec![Fp::from(x)]]).unwrap();
+            prover.assert_satisfied();
+        }
+    }
+
+    #[test]
+    fn test_not_fibonacci() {
+        for x in [4, 7, 100] {
+            let circuit = IsFibonacciCircuit { x: Value::known(Fp::from(x)) };
+            assert!(MockProver::run(9, &circuit, vec![vec![Fp::from(x)]]).is_err());
+        }
+    }
+
+    #[test]
+    fn test_forged_witness() {
+        // 5 * 8^2 + 4 = 18^2
+        assert!(verify_forged(Fp::from(8), Fp::from(18), Fp::one()));
+
+        // The wrong sign breaks the gate.
+        assert!(!verify_forged(Fp::from(8), Fp::from(18), Fp::zero()));
+
+        // The other field root satisfies the gate but not the range check.
+        assert!(!verify_forged(Fp::from(8), -Fp::from(18), Fp::one()));
+
+        // 5x^2 ± 4 may have a root in the field when x is not a Fibonacci
+        // number, but never one below 2^S_BITS.
+        let mut forged = 0;
+        for x in [4u64, 6, 7, 100, 1000] {
+            let five_x_squared = Fp::from(5 * x * x);
+            for (sign, square) in [(Fp::one(), five_x_squared + Fp::from(4)), (Fp::zero(), five_x_squared - Fp::from(4))] {
+                if let Some(s) = Option::<Fp>::from(square.sqrt()) {
+                    assert!(!verify_forged(Fp::from(x), s, sign), "x = {}", x);
+                    assert!(!verify_forged(Fp::from(x), -s, sign), "x = {}", x);
+                    forged += 1;
+                }
+            }
+        }
+        assert!(forged > 0, "some 5x^2 ± 4 has a field root");
+    }
+}
@@ -0,0 +1,289 @@
+/*
+
+    Order-k Fibonacci, e.g. Tribonacci (k = 3):
+
+    0, 0, 1, 1, 2, 4, 7, 13, ...
+
+    | elem_1 | elem_2 | elem_3 | elem_4 | q_kbonacci
+    ------------------------------------------------
+    |    0   |    0   |    1   |    1   |     1
+    |    0   |    1   |    1   |    2   |     1
+    |    1   |    1   |    2   |    4   |     1
+    |        |        |        |        |     0
+
+    q_kbonacci * (elem_1 + ... + elem_k - elem_{k+1}) = 0
+
+    Each row copies the last k cells of the previous row into its first k
+    columns, as `FibonacciChip::assign` does for k = 2.
+
+*/
+
+use std::marker::PhantomData;
+
+use halo2_proofs::arithmetic::FieldExt;
+use halo2_proofs::circuit::{AssignedCell, Chip, Layouter, Value};
+use halo2_proofs::plonk::*;
+use halo2_proofs::poly::Rotation;
+
+/// Computes the n-th term of the order-k sequence seeded with `seeds`, where
+/// k is the number of seeds.
+pub fn nth_term<F: FieldExt>(n: usize, seeds: &[F]) -> F {
+    let mut window = seeds.to_vec();
+    for _ in 0..n {
+        let next = window.iter().fold(F::zero(), |acc, elem| acc + elem);
+        window.remove(0);
+        window.push(next);
+    }
+    window[0]
+}
+
+/// Columns and selector used by the `k-bonacci` gate.
+#[derive(Clone, Debug)]
+pub struct KBonacciConfig {
+    /// The k state columns followed by the column holding their sum.
+    pub elems: Vec<Column<Advice>>,
+    pub q_kbonacci: Selector,
+    pub instance: Column<Instance>,
+}
+
+impl KBonacciConfig {
+    /// The order k of the recurrence.
+    pub fn order(&self) -> usize {
+        self.elems.len() - 1
+    }
+}
+
+/// A chip proving one step of an order-k Fibonacci recurrence per row.
+#[derive(Clone, Debug)]
+pub struct KBonacciChip<F: FieldExt> {
+    config: KBonacciConfig,
+    _marker: PhantomData<F>,
+}
+
+impl<F: FieldExt> Chip<F> for KBonacciChip<F> {
+    type Config = KBonacciConfig;
+    type Loaded = ();
+
+    fn config(&self) -> &Self::Config {
+        &self.config
+    }
+
+    fn loaded(&self) -> &Self::Loaded {
+        &()
+    }
+}
+
+impl<F: FieldExt> KBonacciChip<F> {
+    /// Constructs a chip from a config returned by [`KBonacciChip::configure`].
+    pub fn construct(config: KBonacciConfig) -> Self {
+        Self {
+            config,
+            _marker: PhantomData,
+        }
+    }
+
+    /// Allocates `order + 1` equality-enabled advice columns, a selector and
+    /// an instance column, and creates the `k-bonacci` gate over them.
+    pub fn configure(cs: &mut ConstraintSystem<F>, order: usize) -> KBonacciConfig {
+        assert!(order >= 1, "the recurrence has at least one term");
+
+        let elems: Vec<_> = (0..=order)
+            .map(|_| {
+                let elem = cs.advice_column();
+                cs.enable_equality(elem);
+                elem
+            })
+            .collect();
+        let q_kbonacci = cs.selector();
+        let instance = cs.instance_column();
+        cs.enable_equality(instance);
+
+        cs.create_gate("k-bonacci", |virtual_cells| {
+            let q_kbonacci = virtual_cells.query_selector(q_kbonacci);
+            let elems: Vec<_> = elems
+                .iter()
+                .map(|elem| virtual_cells.query_advice(*elem, Rotation::cur()))
+                .collect();
+            let (sum, terms) = elems.split_last().unwrap();
+            let terms = terms.iter().skip(1).fold(terms[0].clone(), |acc, term| acc + term.clone());
+
+            vec![
+                //     q_kbonacci * (elem_1 + ... + elem_k - elem_{k+1}) = 0
+                q_kbonacci * (terms - sum.clone()),
+            ]
+        });
+
+        KBonacciConfig { elems, q_kbonacci, instance }
+    }
+
+    /// Assigns the first row of the sequence from the k `seeds`, returning the
+    /// last k cells of the row.
+    pub fn init(
+        &self,
+        mut layouter: impl Layouter<F>,
+        seeds: &[Value<F>],
+    ) -> Result<Vec<AssignedCell<F, F>>, Error> {
+        let config = self.config();
+        assert_eq!(seeds.len(), config.order(), "expected one seed per state column");
+
+        layouter.assign_region(|| "init k-bonacci", |mut region| {
+            let offset = 0;
+
+            // Enable q_kbonacci
+            config.q_kbonacci.enable(&mut region, offset)?;
+
+            // Assign the seeds
+            let cells = seeds
+                .iter()
+                .zip(config.elems.iter())
+                .enumerate()
+                .map(|(i, (seed, column))| {
+                    region.assign_advice(|| format!("elem_{}", i + 1), *column, offset, || *seed)
+                })
+                .collect::<Result<Vec<_>, _>>()?;
+
+            let sum = seeds.iter().fold(Value::known(F::zero()), |acc, seed| acc + seed);
+            // Assign the sum
+            let sum = region.assign_advice(|| "sum", *config.elems.last().unwrap(), offset, || sum)?;
+
+            Ok(cells.into_iter().skip(1).chain(Some(sum)).collect())
+        })
+    }
+
+    /// Assigns one more step of the sequence, copying the k cells returned by
+    /// the previous step into the new row and returning its last k cells.
+    pub fn assign(
+        &self,
+        mut layouter: impl Layouter<F>,
+        prev: &[AssignedCell<F, F>],
+    ) -> Result<Vec<AssignedCell<F, F>>, Error> {
+        let config = self.config();
+        assert_eq!(prev.len(), config.order(), "expected one cell per state column");
+
+        layouter.assign_region(|| "steady-state k-bonacci", |mut region| {
+            let offset = 0;
+
+            // Enable q_kbonacci
+            config.q_kbonacci.enable(&mut region, offset)?;
+
+            // Copy the previous state
+            let cells = prev
+                .iter()
+                .zip(config.elems.iter())
+                .enumerate()
+                .map(|(i, (cell, column))| {
+                    cell.copy_advice(|| format!("copy previous state into elem_{}", i + 1), &mut region, *column, offset)
+                })
+                .collect::<Result<Vec<_>, _>>()?;
+
+            let sum = cells.iter().fold(Value::known(F::zero()), |acc, cell| acc + cell.value());
+            // Assign the sum
+            let sum = region.assign_advice(|| "sum", *config.elems.last().unwrap(), offset, || sum)?;
+
+            Ok(cells.into_iter().skip(1).chain(Some(sum)).collect())
+        })
+    }
+
+    /// Constrains `cell` to equal the given `row` of the instance column.
+    pub fn expose_public(
+        &self,
+        mut layouter: impl Layouter<F>,
+        cell: &AssignedCell<F, F>,
+        row: usize,
+    ) -> Result<(), Error> {
+        layouter.constrain_instance(cell.cell(), self.config().instance, row)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use halo2_proofs::{circuit::SimpleFloorPlanner, dev::MockProver, pasta::Fp};
+
+    use super::*;
+
+    #[derive(Clone, Default)]
+    struct KBonacciCircuit<const K: usize> {
+        seeds: Vec<Fp>,
+        n: usize,
+    }
+
+    impl<const K: usize> Circuit<Fp> for KBonacciCircuit<K> {
+        type Config = KBonacciConfig;
+
+        type FloorPlanner = SimpleFloorPlanner;
+
+        fn without_witnesses(&self) -> Self {
+            self.clone()
+        }
+
+        fn configure(meta: &mut ConstraintSystem<Fp>) -> Self::Config {
+            KBonacciChip::configure(meta, K)
+        }
+
+        fn synthesize(&self, config: Self::Config, mut layouter: impl Layouter<Fp>) -> Result<(), Error> {
+            let chip = KBonacciChip::construct(config);
+            let seeds: Vec<_> = self.seeds.iter().copied().map(Value::known).collect();
+
+            let mut state = chip.init(layouter.namespace(|| "init"), &seeds)?;
+            for i in K + 1..=self.n {
+                state = chip.assign(layouter.namespace(|| format!("x_{}", i)), &state)?;
+            }
+
+            chip.expose_public(layouter.namespace(|| "out"), state.last().unwrap(), 0)
+        }
+    }
+
+    fn seeds(values: &[u64]) -> Vec<Fp> {
+        values.iter().copied().map(Fp::from).collect()
+    }
+
+    #[test]
+    fn test_nth_term() {
+        let tribonacci: Vec<_> = (0..11).map(|n| nth_term(n, &seeds(&[0, 0, 1]))).collect();
+        assert_eq!(tribonacci, seeds(&[0, 0, 1, 1, 2, 4, 7, 13, 24, 44, 81]));
+
+        for n in 0..20 {
+            assert_eq!(
+                nth_term(n, &seeds(&[0, 1])),
+                crate::circuit::nth_term(n, Fp::zero(), Fp::one())
+            );
+        }
+    }
+
+    #[test]
+    fn test_tribonacci() {
+        let circuit = KBonacciCircuit::<3> { seeds: seeds(&[0, 0, 1]), n: 10 };
+
+        let prover = MockProver::run(4, &circuit, vec![vec![Fp::from(81)]]).unwrap();
+        prover.assert_satisfied();
+
+        let prover = MockProver::run(4, &circuit, vec![vec![Fp::from(82)]]).unwrap();
+        assert!(prover.verify().is_err());
+    }
+
+    #[test]
+    fn test_tetranacci() {
+        let circuit = KBonacciCircuit::<4> { seeds: seeds(&[0, 0, 0, 1]), n: 11 };
+
+        let prover = MockProver::run(4, &circuit, vec![vec![Fp::from(108)]]).unwrap();
+        prover.assert_satisfied();
+    }
+
+    #[test]
+    fn test_many_orders() {
+        fn check<const K: usize>() {
+            let seeds: Vec<_> = (1..=K as u64).map(Fp::from).collect();
+            let n = 3 * K;
+            let output = nth_term(n, &seeds);
+
+            let circuit = KBonacciCircuit::<K> { seeds, n };
+            let prover = MockProver::run(5, &circuit, vec![vec![output]]).unwrap();
+            prover.assert_satisfied();
+        }
+
+        check::<1>();
+        check::<2>();
+        check::<5>();
+        check::<8>();
+    }
+}
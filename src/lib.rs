@@ -1 +1,46 @@
-mod fibonacci;
\ No newline at end of file
+//! Fibonacci circuits built with the Halo 2 proof system.
+//!
+//! The [`fibonacci`] module contains the chip from the A New HOPE 2022
+//! workshop, which downstream circuits can use to prove terms of a
+//! Fibonacci-style sequence. [`circuit`] wraps it into a circuit for the n-th
+//! term, [`proof`] creates and verifies real proofs of that circuit, and
+//! [`bundle`] stores them on disk. [`cache`] keeps parameters between runs.
+//!
+//! [`recurrence`] generalises the chip to other linear recurrences such as
+//! the Lucas, Pell and Jacobsthal sequences, and [`kbonacci`] to recurrences
+//! of any order such as Tribonacci. [`fast_doubling`] proves F(n) in
+//! O(log n) rows, as does [`matrix`] by exponentiating the Fibonacci
+//! Q-matrix. [`single_column`] and [`two_column`] lay the linear chain out
+//! using rotations instead of copy constraints; any layout implementing
+//! [`circuit::FibonacciLayout`] can be proven with [`circuit::LayoutCircuit`].
+//! [`wide`] packs several steps into each row to fit longer chains into a
+//! fixed `k`, and [`batch`] proves many independent chains side by side.
+//! [`hidden_index`] proves that a number is in the Fibonacci sequence
+//! without revealing its index, and [`is_fibonacci`] without computing the
+//! sequence at all, using the lookup-based [`range_check`] chip. [`integer`]
+//! uses the same range checks to prove integer Fibonacci numbers rather than
+//! their residues modulo the field, [`wrapping`] to match
+//! `u64::wrapping_add`, and [`bigint`] to prove terms larger than the field
+//! modulus limb by limb. [`modular`] proves the sequence modulo a public
+//! modulus instead.
+
+pub mod batch;
+pub mod bigint;
+pub mod bundle;
+pub mod cache;
+pub mod circuit;
+pub mod fast_doubling;
+pub mod fibonacci;
+pub mod hidden_index;
+pub mod integer;
+pub mod is_fibonacci;
+pub mod kbonacci;
+pub mod matrix;
+pub mod modular;
+pub mod proof;
+pub mod range_check;
+pub mod recurrence;
+pub mod single_column;
+pub mod two_column;
+pub mod wide;
+pub mod wrapping;
@@ -0,0 +1,248 @@
+//! Command-line interface for proving and verifying the n-th Fibonacci number.
+//!
+//! ```text
+//! halo2-hope setup   --n <N> [--params params.bin]
+//! halo2-hope prove   --n <N> --seed <a,b> [--params params.bin] [--proof proof.bin]
+//! halo2-hope verify  [--params params.bin] [--proof proof.bin]
+//! halo2-hope inspect [--proof proof.bin]
+//! ```
+//!
+//! The prover and verifier only share files: proofs are written as a
+//! [`ProofBundle`] carrying the public inputs and `n`, from which the verifier
+//! regenerates the verifying key. The public inputs are the n-th term and the
+//! seeds it was computed from, so a proof for the seeds `0,1` is a proof of
+//! F(n) and nothing else.
+
+use std::collections::HashMap;
+use std::fs::File;
+use std::io::{BufReader, BufWriter, Write};
+use std::process;
+
+use halo2_hope::circuit::FibonacciCircuit;
+use halo2_hope::bundle::ProofBundle;
+use halo2_hope::proof::{self, Proof};
+use halo2_proofs::arithmetic::FieldExt;
+use halo2_proofs::circuit::Value;
+use halo2_proofs::pasta::group::ff::PrimeField;
+use halo2_proofs::pasta::{EqAffine, Fp};
+use halo2_proofs::poly::commitment::Params;
+
+type Result<T> = std::result::Result<T, Box<dyn std::error::Error>>;
+
+const USAGE: &str = "usage:
+    halo2-hope setup   --n <N> [--params params.bin]
+    halo2-hope prove   --n <N> --seed <a,b> [--params params.bin] [--proof proof.bin]
+    halo2-hope verify  [--params params.bin] [--proof proof.bin]
+    halo2-hope inspect [--proof proof.bin]";
+
+/// Parsed `--key value` options of a subcommand.
+struct Options(HashMap<String, String>);
+
+impl Options {
+    fn parse(args: &[String], allowed: &[&str]) -> Result<Self> {
+        let mut options = HashMap::new();
+        let mut args = args.iter();
+        while let Some(key) = args.next() {
+            let name = key
+                .strip_prefix("--")
+                .filter(|name| allowed.contains(name))
+                .ok_or_else(|| format!("unexpected argument `{}`", key))?;
+            let value = args.next().ok_or_else(|| format!("missing value for `{}`", key))?;
+            options.insert(name.to_string(), value.clone());
+        }
+        Ok(Options(options))
+    }
+
+    fn get<'a>(&'a self, name: &str, default: &'a str) -> &'a str {
+        self.0.get(name).map(String::as_str).unwrap_or(default)
+    }
+
+    fn required(&self, name: &str) -> Result<&str> {
+        Ok(self.0.get(name).ok_or_else(|| format!("missing `--{}`", name))?)
+    }
+
+    fn n(&self) -> Result<usize> {
+        let n: usize = self.required("n")?.parse()?;
+        if n < 2 {
+            return Err("`--n` must be at least 2".into());
+        }
+        Ok(n)
+    }
+}
+
+/// Formats a field element as big-endian hex, like its `Debug` output.
+fn fp_to_hex(value: &Fp) -> String {
+    let repr = value.to_repr();
+    let hex: String = repr.as_ref().iter().rev().map(|byte| format!("{:02x}", byte)).collect();
+    format!("0x{}", hex)
+}
+
+/// Describes what `proof` shows, e.g. `x_10 = 0x…37 from seeds (0x…00, 0x…01)`.
+fn claim(proof: &Proof) -> String {
+    format!(
+        "x_{} = {} from seeds ({}, {})",
+        proof.n,
+        fp_to_hex(&proof.output),
+        fp_to_hex(&proof.seeds.0),
+        fp_to_hex(&proof.seeds.1)
+    )
+}
+
+/// Checks `bundle` against the verifying key regenerated from `params`,
+/// returning the proof it contains.
+fn check_bundle(params: &Params<EqAffine>, bundle: &ProofBundle) -> Result<Proof> {
+    let proof = bundle.to_proof()?;
+    let pk = proof::keygen(params, proof.n)?;
+    bundle.validate(params, pk.get_vk())?;
+    proof::verify(params, pk.get_vk(), &bundle.public_inputs, &proof)?;
+    Ok(proof)
+}
+
+fn read_bundle(path: &str) -> Result<ProofBundle> {
+    let bundle = ProofBundle::read(&mut BufReader::new(File::open(path)?))?;
+    if bundle.n < 2 {
+        return Err(format!("{}: invalid n = {}", path, bundle.n).into());
+    }
+    Ok(bundle)
+}
+
+/// The `k` of the circuit proving the n-th term.
+fn circuit_k(n: usize) -> u32 {
+    FibonacciCircuit::<Fp>::new(n, Value::unknown(), Value::unknown()).k()
+}
+
+/// Reads the parameters at `path`, rejecting them unless they are for `k`.
+fn read_params(path: &str, k: u32) -> Result<Params<EqAffine>> {
+    let mut reader = BufReader::new(File::open(path)?);
+    proof::read_params(&mut reader, k).map_err(|e| format!("{}: {}", path, e).into())
+}
+
+fn setup(args: &[String]) -> Result<()> {
+    let options = Options::parse(args, &["n", "params"])?;
+    let params_path = options.get("params", "params.bin");
+
+    let n = options.n()?;
+    let params = proof::setup(n);
+    params.write(&mut BufWriter::new(File::create(params_path)?))?;
+
+    let k = circuit_k(n);
+    println!("wrote parameters for k = {} to {}", k, params_path);
+    Ok(())
+}
+
+fn prove(args: &[String]) -> Result<()> {
+    let options = Options::parse(args, &["n", "seed", "params", "proof"])?;
+    let n = options.n()?;
+    let (a, b) = options
+        .required("seed")?
+        .split_once(',')
+        .ok_or("`--seed` must be of the form `a,b`")?;
+    let seeds = (Fp::from_u128(a.trim().parse()?), Fp::from_u128(b.trim().parse()?));
+
+    let params = read_params(options.get("params", "params.bin"), circuit_k(n))?;
+    let pk = proof::keygen(&params, n)?;
+    let proof = proof::prove(&params, &pk, n, seeds)?;
+
+    let proof_path = options.get("proof", "proof.bin");
+    let bundle = ProofBundle::new(&params, pk.get_vk(), &proof);
+    let mut writer = BufWriter::new(File::create(proof_path)?);
+    bundle.write(&mut writer)?;
+    writer.flush()?;
+
+    println!("{}", claim(&proof));
+    println!("wrote proof to {}", proof_path);
+    Ok(())
+}
+
+fn verify(args: &[String]) -> Result<()> {
+    let options = Options::parse(args, &["params", "proof"])?;
+    let bundle = read_bundle(options.get("proof", "proof.bin"))?;
+
+    let params = read_params(options.get("params", "params.bin"), bundle.k)?;
+    let proof = check_bundle(&params, &bundle)?;
+
+    println!("verified {}", claim(&proof));
+    Ok(())
+}
+
+fn inspect(args: &[String]) -> Result<()> {
+    let options = Options::parse(args, &["proof"])?;
+    let bundle = read_bundle(options.get("proof", "proof.bin"))?;
+
+    println!("circuit        {}", bundle.circuit_id);
+    println!("k              {}", bundle.k);
+    println!("n              {}", bundle.n);
+    for (i, input) in bundle.public_inputs.iter().enumerate() {
+        println!("public input {} {}", i, fp_to_hex(input));
+    }
+    println!("proof size     {} bytes", bundle.proof.len());
+    let fingerprint: String = bundle.vk_fingerprint.iter().map(|byte| format!("{:02x}", byte)).collect();
+    println!("vk fingerprint {}", fingerprint);
+    Ok(())
+}
+
+fn main() {
+    let args: Vec<String> = std::env::args().skip(1).collect();
+
+    let result = match args.first().map(String::as_str) {
+        Some("setup") => setup(&args[1..]),
+        Some("prove") => prove(&args[1..]),
+        Some("verify") => verify(&args[1..]),
+        Some("inspect") => inspect(&args[1..]),
+        _ => {
+            eprintln!("{}", USAGE);
+            process::exit(2);
+        }
+    };
+
+    if let Err(e) = result {
+        eprintln!("error: {}", e);
+        process::exit(1);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_fp_to_hex() {
+        for value in [Fp::zero(), Fp::one(), Fp::from(55), -Fp::one()] {
+            assert_eq!(fp_to_hex(&value), format!("{:?}", value));
+        }
+    }
+
+    #[test]
+    fn test_options() {
+        let args: Vec<String> = ["--n", "10", "--seed", "0,1"].iter().map(|s| s.to_string()).collect();
+
+        let options = Options::parse(&args, &["n", "seed"]).unwrap();
+        assert_eq!(options.n().unwrap(), 10);
+        assert_eq!(options.get("params", "params.bin"), "params.bin");
+
+        assert!(Options::parse(&args, &["n"]).is_err());
+        assert!(Options::parse(&args[..1], &["n"]).is_err());
+    }
+
+    #[test]
+    fn test_chosen_seeds() {
+        let params = proof::setup(10);
+        let pk = proof::keygen(&params, 10).unwrap();
+
+        // x_10 = 34 * x_0 + 55 * x_1, so these seeds reach 12345 instead of F(10).
+        let proof = proof::prove(&params, &pk, 10, (Fp::from(355), Fp::from(5))).unwrap();
+        let bundle = ProofBundle::new(&params, pk.get_vk(), &proof);
+
+        let mut bytes = vec![];
+        bundle.write(&mut bytes).unwrap();
+        let read = ProofBundle::read(&mut &bytes[..]).unwrap();
+        let verified = check_bundle(&params, &read).unwrap();
+        assert_eq!(verified.seeds, (Fp::from(355), Fp::from(5)));
+        assert!(claim(&verified).ends_with(&format!("from seeds ({}, {})", fp_to_hex(&Fp::from(355)), fp_to_hex(&Fp::from(5)))));
+
+        // The same proof does not verify as F(10) = 12345.
+        let mut forged = read;
+        forged.public_inputs = vec![Fp::from(12345), Fp::zero(), Fp::one()];
+        assert!(check_bundle(&params, &forged).is_err());
+    }
+}
@@ -0,0 +1,314 @@
+/*
+
+    2x2 matrix multiplication C = A * B, with each matrix in one row of the
+    four columns m00, m01, m10, m11:
+
+    | m00 | m01 | m10 | m11 | q_mul
+    -------------------------------
+    | a00 | a01 | a10 | a11 |   1
+    | b00 | b01 | b10 | b11 |   0
+    | c00 | c01 | c10 | c11 |   0
+
+    q_mul * (a00 * b00 + a01 * b10 - c00) = 0
+    q_mul * (a00 * b01 + a01 * b11 - c01) = 0
+    q_mul * (a10 * b00 + a11 * b10 - c10) = 0
+    q_mul * (a10 * b01 + a11 * b11 - c11) = 0
+
+    Powers are computed by square-and-multiply over the bits of the exponent,
+    so the layout depends on the exponent. With the Q-matrix
+
+    Q = | 1  1 |    Q^n = | F(n + 1)  F(n)     |
+        | 1  0 |          | F(n)      F(n - 1) |
+
+*/
+
+use std::marker::PhantomData;
+
+use halo2_proofs::arithmetic::FieldExt;
+use halo2_proofs::circuit::{AssignedCell, Chip, Layouter};
+use halo2_proofs::plonk::*;
+use halo2_proofs::poly::Rotation;
+
+/// A 2x2 matrix in row-major order.
+pub type Matrix<F> = [F; 4];
+
+/// A 2x2 matrix of assigned cells in row-major order.
+pub type AssignedMatrix<F> = [AssignedCell<F, F>; 4];
+
+/// The Fibonacci Q-matrix.
+pub fn q_matrix<F: FieldExt>() -> Matrix<F> {
+    [F::one(), F::one(), F::one(), F::zero()]
+}
+
+/// Multiplies two matrices natively.
+pub fn mul<F: FieldExt>(a: &Matrix<F>, b: &Matrix<F>) -> Matrix<F> {
+    [
+        a[0] * b[0] + a[1] * b[2],
+        a[0] * b[1] + a[1] * b[3],
+        a[2] * b[0] + a[3] * b[2],
+        a[2] * b[1] + a[3] * b[3],
+    ]
+}
+
+/// Computes `base^n` natively.
+pub fn pow<F: FieldExt>(base: &Matrix<F>, n: u64) -> Matrix<F> {
+    (0..64).rev().fold([F::one(), F::zero(), F::zero(), F::one()], |acc, i| {
+        let acc = mul(&acc, &acc);
+        if (n >> i) & 1 == 1 {
+            mul(&acc, base)
+        } else {
+            acc
+        }
+    })
+}
+
+/// Columns and selector used by the `matrix multiplication` gate.
+#[derive(Clone, Debug, Copy)]
+pub struct MatrixConfig {
+    pub entries: [Column<Advice>; 4],
+    pub constants: Column<Fixed>,
+    pub q_mul: Selector,
+    pub instance: Column<Instance>,
+}
+
+/// A chip multiplying and exponentiating 2x2 matrices.
+#[derive(Clone, Debug)]
+pub struct MatrixChip<F: FieldExt> {
+    config: MatrixConfig,
+    _marker: PhantomData<F>,
+}
+
+impl<F: FieldExt> Chip<F> for MatrixChip<F> {
+    type Config = MatrixConfig;
+    type Loaded = ();
+
+    fn config(&self) -> &Self::Config {
+        &self.config
+    }
+
+    fn loaded(&self) -> &Self::Loaded {
+        &()
+    }
+}
+
+impl<F: FieldExt> MatrixChip<F> {
+    /// Constructs a chip from a config returned by [`MatrixChip::configure`].
+    pub fn construct(config: MatrixConfig) -> Self {
+        Self {
+            config,
+            _marker: PhantomData,
+        }
+    }
+
+    /// Allocates four equality-enabled advice columns, a constants column, a
+    /// selector and an instance column, and creates the
+    /// `matrix multiplication` gate over them.
+    pub fn configure(cs: &mut ConstraintSystem<F>) -> MatrixConfig {
+        let entries = [(); 4].map(|_| {
+            let column = cs.advice_column();
+            cs.enable_equality(column);
+            column
+        });
+        let constants = cs.fixed_column();
+        cs.enable_constant(constants);
+        let q_mul = cs.selector();
+        let instance = cs.instance_column();
+        cs.enable_equality(instance);
+
+        cs.create_gate("matrix multiplication", |virtual_cells| {
+            let q_mul = virtual_cells.query_selector(q_mul);
+            let a = entries.map(|column| virtual_cells.query_advice(column, Rotation::cur()));
+            let b = entries.map(|column| virtual_cells.query_advice(column, Rotation::next()));
+            let c = entries.map(|column| virtual_cells.query_advice(column, Rotation(2)));
+
+            let entry = |i: usize, j: usize| {
+                a[2 * i].clone() * b[j].clone() + a[2 * i + 1].clone() * b[2 + j].clone() - c[2 * i + j].clone()
+            };
+
+            Constraints::with_selector(
+                q_mul,
+                [("c00", entry(0, 0)), ("c01", entry(0, 1)), ("c10", entry(1, 0)), ("c11", entry(1, 1))],
+            )
+        });
+
+        MatrixConfig { entries, constants, q_mul, instance }
+    }
+
+    /// Assigns a constant matrix, fixed in the verifying key.
+    pub fn load_constant(&self, mut layouter: impl Layouter<F>, matrix: Matrix<F>) -> Result<AssignedMatrix<F>, Error> {
+        let config = self.config();
+
+        layouter.assign_region(|| "load matrix", |mut region| {
+            let cells = config
+                .entries
+                .iter()
+                .zip(matrix.iter())
+                .map(|(column, entry)| region.assign_advice_from_constant(|| "entry", *column, 0, *entry))
+                .collect::<Result<Vec<_>, _>>()?;
+            Ok(cells.try_into().unwrap())
+        })
+    }
+
+    /// Assigns `a * b`, copying `a` and `b` into the multiplication region.
+    pub fn mul(
+        &self,
+        mut layouter: impl Layouter<F>,
+        a: &AssignedMatrix<F>,
+        b: &AssignedMatrix<F>,
+    ) -> Result<AssignedMatrix<F>, Error> {
+        let config = self.config();
+
+        layouter.assign_region(|| "matrix multiplication", |mut region| {
+            // Enable q_mul
+            config.q_mul.enable(&mut region, 0)?;
+
+            // Copy a and b
+            for (row, matrix) in [a, b].into_iter().enumerate() {
+                for (cell, column) in matrix.iter().zip(config.entries.iter()) {
+                    cell.copy_advice(|| "copy factor", &mut region, *column, row)?;
+                }
+            }
+
+            // Assign c
+            let c = a[0].value().zip(a[1].value()).zip(a[2].value()).zip(a[3].value());
+            let d = b[0].value().zip(b[1].value()).zip(b[2].value()).zip(b[3].value());
+            let product = c.zip(d).map(|((((a0, a1), a2), a3), (((b0, b1), b2), b3))| {
+                mul(&[*a0, *a1, *a2, *a3], &[*b0, *b1, *b2, *b3])
+            });
+            let cells = config
+                .entries
+                .iter()
+                .enumerate()
+                .map(|(i, column)| region.assign_advice(|| "product", *column, 2, || product.map(|product| product[i])))
+                .collect::<Result<Vec<_>, _>>()?;
+            Ok(cells.try_into().unwrap())
+        })
+    }
+
+    /// Assigns `base^n` by square-and-multiply over the bits of `n`.
+    pub fn pow(&self, mut layouter: impl Layouter<F>, base: &AssignedMatrix<F>, n: u64) -> Result<AssignedMatrix<F>, Error> {
+        if n == 0 {
+            return self.load_constant(layouter.namespace(|| "identity"), [F::one(), F::zero(), F::zero(), F::one()]);
+        }
+
+        // Start from the most significant bit, which is always set.
+        let mut acc = base.clone();
+        for i in (0..63 - n.leading_zeros()).rev() {
+            acc = self.mul(layouter.namespace(|| format!("square for bit {}", i)), &acc, &acc)?;
+            if (n >> i) & 1 == 1 {
+                acc = self.mul(layouter.namespace(|| format!("multiply for bit {}", i)), &acc, base)?;
+            }
+        }
+        Ok(acc)
+    }
+
+    /// Constrains `cell` to equal the given `row` of the instance column.
+    pub fn expose_public(
+        &self,
+        mut layouter: impl Layouter<F>,
+        cell: &AssignedCell<F, F>,
+        row: usize,
+    ) -> Result<(), Error> {
+        layouter.constrain_instance(cell.cell(), self.config().instance, row)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use halo2_proofs::{circuit::SimpleFloorPlanner, dev::MockProver, pasta::Fp};
+
+    use super::*;
+    use crate::circuit::cross_check::{CrossCheckCircuit, NthFibonacci};
+    use crate::circuit::nth_term;
+    use crate::fast_doubling::fast_doubling;
+
+    #[derive(Clone, Default)]
+    struct MatrixPowCircuit {
+        base: Matrix<Fp>,
+        n: u64,
+    }
+
+    impl Circuit<Fp> for MatrixPowCircuit {
+        type Config = MatrixConfig;
+
+        type FloorPlanner = SimpleFloorPlanner;
+
+        fn without_witnesses(&self) -> Self {
+            self.clone()
+        }
+
+        fn configure(meta: &mut ConstraintSystem<Fp>) -> Self::Config {
+            MatrixChip::configure(meta)
+        }
+
+        fn synthesize(&self, config: Self::Config, mut layouter: impl Layouter<Fp>) -> Result<(), Error> {
+            let chip = MatrixChip::construct(config);
+
+            let base = chip.load_constant(layouter.namespace(|| "base"), self.base)?;
+            let power = chip.pow(layouter.namespace(|| "pow"), &base, self.n)?;
+
+            // The top-right entry is x_n for the recurrence of `base`
+            chip.expose_public(layouter.namespace(|| "out"), &power[1], 0)
+        }
+    }
+
+    impl NthFibonacci for MatrixChip<Fp> {
+        type Config = MatrixConfig;
+
+        fn configure(meta: &mut ConstraintSystem<Fp>) -> Self::Config {
+            MatrixChip::configure(meta)
+        }
+
+        fn assign_nth_term(
+            config: Self::Config,
+            mut layouter: impl Layouter<Fp>,
+            n: usize,
+        ) -> Result<AssignedCell<Fp, Fp>, Error> {
+            let matrix = MatrixChip::construct(config);
+
+            let q = matrix.load_constant(layouter.namespace(|| "Q"), q_matrix())?;
+            let power = matrix.pow(layouter.namespace(|| "Q^n"), &q, n as u64)?;
+            Ok(power[1].clone())
+        }
+    }
+
+    #[test]
+    fn test_native() {
+        for n in 0..100 {
+            assert_eq!(pow(&q_matrix::<Fp>(), n)[1], nth_term(n as usize, Fp::zero(), Fp::one()));
+        }
+    }
+
+    #[test]
+    fn test_agrees_with_linear_chain() {
+        for n in 2..=40 {
+            let circuit = CrossCheckCircuit::<MatrixChip<Fp>>::new(n);
+            let prover = MockProver::run(7, &circuit, vec![vec![], vec![]]).unwrap();
+            prover.assert_satisfied();
+        }
+    }
+
+    #[test]
+    fn test_q_matrix_power() {
+        for n in [0, 1, 2, 11, 93, 1 << 40, u64::MAX] {
+            let circuit = MatrixPowCircuit { base: q_matrix(), n };
+            let output = fast_doubling::<Fp>(n).0;
+
+            let prover = MockProver::run(9, &circuit, vec![vec![output]]).unwrap();
+            prover.assert_satisfied();
+
+            let prover = MockProver::run(9, &circuit, vec![vec![output + Fp::one()]]).unwrap();
+            assert!(prover.verify().is_err());
+        }
+    }
+
+    #[test]
+    fn test_pell() {
+        // Pell numbers satisfy x_{n+2} = 2 x_{n+1} + x_n.
+        let base = [Fp::from(2), Fp::one(), Fp::one(), Fp::zero()];
+        let circuit = MatrixPowCircuit { base, n: 10 };
+
+        let prover = MockProver::run(6, &circuit, vec![vec![Fp::from(2378)]]).unwrap();
+        prover.assert_satisfied();
+    }
+}
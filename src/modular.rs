@@ -0,0 +1,474 @@
+/*
+
+    x_{i+2} = (x_i + x_{i+1}) mod m for a public m, e.g. m = 7:
+
+    | elem_1 | elem_2 | elem_3 | quotient | modulus | q_mod | q_lt
+    --------------------------------------------------------------
+    |    5   |    1   |    6   |    0     |    7    |   1   |   0
+    |    1   |    6   |    0   |    1     |    7    |   1   |   0
+
+    q_mod * quotient * (1 - quotient) = 0
+    q_mod * (elem_1 + elem_2 - elem_3 - quotient * modulus) = 0
+
+    Every element is checked to be below m in a row of its own, with the
+    element in elem_1 and the difference in elem_3:
+
+    | elem_1 | elem_2 | elem_3 | quotient | modulus | q_mod | q_lt
+    --------------------------------------------------------------
+    |    6   |        |    0   |          |    7    |   0   |   1
+
+    q_lt * (modulus - 1 - elem_1 - elem_3) = 0
+
+    and both elem_1 and elem_3 of that row range-checked below 2^B, as is m.
+    With every element below m < 2^B the sum gate cannot wrap around the
+    field, so elem_3 is the integer (elem_1 + elem_2) mod m.
+
+    `ModularCircuit` copies m and the two seeds from the instance column, so
+    the verifier knows both the modulus and where the chain starts.
+
+*/
+
+use std::marker::PhantomData;
+
+use halo2_proofs::arithmetic::FieldExt;
+use halo2_proofs::circuit::{AssignedCell, Chip, Layouter, Region, SimpleFloorPlanner, Value};
+use halo2_proofs::plonk::*;
+use halo2_proofs::poly::Rotation;
+
+use crate::circuit::min_k;
+use crate::fibonacci::FibonacciCells;
+use crate::range_check::{fits_in_bits, low_u64, RangeCheckChip, RangeCheckConfig, K};
+
+/// Computes the n-th term of the sequence seeded with `(elem_1, elem_2)`
+/// modulo `m`.
+pub fn nth_term(n: usize, elem_1: u64, elem_2: u64, m: u64) -> u64 {
+    let (mut a, mut b) = (elem_1 % m, elem_2 % m);
+    for _ in 0..n {
+        (a, b) = (b, ((a as u128 + b as u128) % m as u128) as u64);
+    }
+    a
+}
+
+/// Columns and selectors used by the `fibonacci mod m` and `below modulus`
+/// gates.
+#[derive(Clone, Debug, Copy)]
+pub struct ModularConfig {
+    pub elem_1: Column<Advice>,
+    pub elem_2: Column<Advice>,
+    pub elem_3: Column<Advice>,
+    pub quotient: Column<Advice>,
+    pub modulus: Column<Advice>,
+    pub q_mod: Selector,
+    pub q_lt: Selector,
+    pub instance: Column<Instance>,
+    pub range_check: RangeCheckConfig,
+}
+
+/// A chip proving one Fibonacci step modulo a public m per row.
+#[derive(Clone, Debug)]
+pub struct ModularChip<F: FieldExt> {
+    config: ModularConfig,
+    num_bits: usize,
+    _marker: PhantomData<F>,
+}
+
+impl<F: FieldExt> Chip<F> for ModularChip<F> {
+    type Config = ModularConfig;
+    type Loaded = ();
+
+    fn config(&self) -> &Self::Config {
+        &self.config
+    }
+
+    fn loaded(&self) -> &Self::Loaded {
+        &()
+    }
+}
+
+impl<F: FieldExt> ModularChip<F> {
+    /// Constructs a chip from a config returned by [`ModularChip::configure`],
+    /// for moduli below 2^`num_bits`. `num_bits` must be a multiple of K of at
+    /// most 64.
+    pub fn construct(config: ModularConfig, num_bits: usize) -> Self {
+        assert_eq!(num_bits % K, 0, "num_bits is a multiple of K");
+        assert!(num_bits <= 64, "the modulus is a u64");
+        Self {
+            config,
+            num_bits,
+            _marker: PhantomData,
+        }
+    }
+
+    /// Allocates five equality-enabled advice columns, two selectors and an
+    /// instance column, and creates the `fibonacci mod m` and
+    /// `below modulus` gates over them. Also configures a [`RangeCheckChip`].
+    pub fn configure(cs: &mut ConstraintSystem<F>) -> ModularConfig {
+        let mut advice = || {
+            let column = cs.advice_column();
+            cs.enable_equality(column);
+            column
+        };
+        let (elem_1, elem_2, elem_3, quotient, modulus) = (advice(), advice(), advice(), advice(), advice());
+        let q_mod = cs.selector();
+        let q_lt = cs.selector();
+        let instance = cs.instance_column();
+        cs.enable_equality(instance);
+        let range_check = RangeCheckChip::configure(cs);
+
+        cs.create_gate("fibonacci mod m", |virtual_cells| {
+            let q_mod = virtual_cells.query_selector(q_mod);
+            let elem_1 = virtual_cells.query_advice(elem_1, Rotation::cur());
+            let elem_2 = virtual_cells.query_advice(elem_2, Rotation::cur());
+            let elem_3 = virtual_cells.query_advice(elem_3, Rotation::cur());
+            let quotient = virtual_cells.query_advice(quotient, Rotation::cur());
+            let modulus = virtual_cells.query_advice(modulus, Rotation::cur());
+            let one = Expression::Constant(F::one());
+
+            Constraints::with_selector(
+                q_mod,
+                [
+                    ("quotient is boolean", quotient.clone() * (one - quotient.clone())),
+                    ("sum", elem_1 + elem_2 - elem_3 - quotient * modulus),
+                ],
+            )
+        });
+
+        cs.create_gate("below modulus", |virtual_cells| {
+            let q_lt = virtual_cells.query_selector(q_lt);
+            let value = virtual_cells.query_advice(elem_1, Rotation::cur());
+            let diff = virtual_cells.query_advice(elem_3, Rotation::cur());
+            let modulus = virtual_cells.query_advice(modulus, Rotation::cur());
+            let one = Expression::Constant(F::one());
+
+            vec![
+                //     q_lt * (modulus - 1 - elem_1 - elem_3) = 0
+                q_lt * (modulus - one - value - diff),
+            ]
+        });
+
+        ModularConfig { elem_1, elem_2, elem_3, quotient, modulus, q_mod, q_lt, instance, range_check }
+    }
+
+    /// Loads the range check table. Must be called once per circuit.
+    pub fn load_table(&self, layouter: impl Layouter<F>) -> Result<(), Error> {
+        RangeCheckChip::construct(self.config().range_check).load_table(layouter)
+    }
+
+    /// Copies the modulus from the given `row` of the instance column and
+    /// range-checks it below 2^`num_bits`.
+    ///
+    /// Returns [`Error::Synthesis`] if the modulus is known and is 0 or not
+    /// below 2^`num_bits`.
+    pub fn load_modulus(&self, mut layouter: impl Layouter<F>, row: usize) -> Result<AssignedCell<F, F>, Error> {
+        let config = self.config();
+        let range_check = RangeCheckChip::construct(config.range_check);
+
+        let modulus = layouter.assign_region(|| "load modulus", |mut region| {
+            region.assign_advice_from_instance(|| "modulus", config.instance, row, config.modulus, 0)
+        })?;
+        modulus.value().error_if_known_and(|m| **m == F::zero() || !fits_in_bits(*m, self.num_bits))?;
+        range_check.copy_check(layouter.namespace(|| "range check modulus"), &modulus, self.num_bits)?;
+        Ok(modulus)
+    }
+
+    /// Constrains `cell` to be below `modulus`.
+    fn check_below(
+        &self,
+        mut layouter: impl Layouter<F>,
+        cell: &AssignedCell<F, F>,
+        modulus: &AssignedCell<F, F>,
+    ) -> Result<(), Error> {
+        let config = self.config();
+        let range_check = RangeCheckChip::construct(config.range_check);
+
+        let (value, diff) = layouter.assign_region(|| "below modulus", |mut region| {
+            let offset = 0;
+
+            // Enable q_lt
+            config.q_lt.enable(&mut region, offset)?;
+
+            let value = cell.copy_advice(|| "value", &mut region, config.elem_1, offset)?;
+            let modulus = modulus.copy_advice(|| "modulus", &mut region, config.modulus, offset)?;
+
+            let diff = modulus.value().copied() - Value::known(F::one()) - value.value();
+            let diff = region.assign_advice(|| "modulus - 1 - value", config.elem_3, offset, || diff)?;
+
+            Ok((value, diff))
+        })?;
+
+        range_check.copy_check(layouter.namespace(|| "range check value"), &value, self.num_bits)?;
+        range_check.copy_check(layouter.namespace(|| "range check difference"), &diff, self.num_bits)
+    }
+
+    /// Assigns `elem_3 = (elem_1 + elem_2) mod m` and its quotient in one row,
+    /// returning the cells holding `elem_1`, `elem_2` and `elem_3`.
+    fn assign_row(
+        &self,
+        mut layouter: impl Layouter<F>,
+        name: &str,
+        modulus: &AssignedCell<F, F>,
+        elem_1: impl Fn(&mut Region<'_, F>) -> Result<AssignedCell<F, F>, Error>,
+        elem_2: impl Fn(&mut Region<'_, F>) -> Result<AssignedCell<F, F>, Error>,
+    ) -> Result<(AssignedCell<F, F>, FibonacciCells<F>), Error> {
+        let config = self.config();
+
+        layouter.assign_region(|| name, |mut region| {
+            let offset = 0;
+
+            // Enable q_mod
+            config.q_mod.enable(&mut region, offset)?;
+
+            let modulus = modulus.copy_advice(|| "modulus", &mut region, config.modulus, offset)?;
+            let elem_1 = elem_1(&mut region)?;
+            let elem_2 = elem_2(&mut region)?;
+
+            let sum = elem_1.value().zip(elem_2.value()).zip(modulus.value()).map(|((a, b), m)| {
+                let (sum, m) = (low_u64(a) as u128 + low_u64(b) as u128, low_u64(m) as u128);
+                (F::from_u128(sum % m), F::from_u128(sum / m))
+            });
+            let (elem_3, quotient) = sum.unzip();
+
+            // Assign elem_3 and quotient
+            let elem_3 = region.assign_advice(|| "elem_3", config.elem_3, offset, || elem_3)?;
+            region.assign_advice(|| "quotient", config.quotient, offset, || quotient)?;
+
+            Ok((elem_1, (elem_2, elem_3)))
+        })
+    }
+
+    /// Assigns the first row of the chain from the two seeds `elem_1` and
+    /// `elem_2`, constraining the seeds and their sum modulo `modulus` to be
+    /// below `modulus`.
+    pub fn init(
+        &self,
+        layouter: impl Layouter<F>,
+        modulus: &AssignedCell<F, F>,
+        elem_1: Value<F>,
+        elem_2: Value<F>,
+    ) -> Result<FibonacciCells<F>, Error> {
+        let config = self.config();
+
+        self.init_row(
+            layouter,
+            "init Fibonacci mod m",
+            modulus,
+            |region| region.assign_advice(|| "elem_1", config.elem_1, 0, || elem_1),
+            |region| region.assign_advice(|| "elem_2", config.elem_2, 0, || elem_2),
+        )
+    }
+
+    /// Like [`ModularChip::init`], but with the seeds copied from rows
+    /// `elem_1_row` and `elem_2_row` of the instance column, so that the
+    /// verifier knows where the chain starts.
+    pub fn init_from_instance(
+        &self,
+        layouter: impl Layouter<F>,
+        modulus: &AssignedCell<F, F>,
+        elem_1_row: usize,
+        elem_2_row: usize,
+    ) -> Result<FibonacciCells<F>, Error> {
+        let config = self.config();
+
+        self.init_row(
+            layouter,
+            "init public Fibonacci mod m",
+            modulus,
+            |region| region.assign_advice_from_instance(|| "elem_1", config.instance, elem_1_row, config.elem_1, 0),
+            |region| region.assign_advice_from_instance(|| "elem_2", config.instance, elem_2_row, config.elem_2, 0),
+        )
+    }
+
+    /// Assigns the first row of the chain and constrains all three of its
+    /// elements to be below `modulus`.
+    fn init_row(
+        &self,
+        mut layouter: impl Layouter<F>,
+        name: &str,
+        modulus: &AssignedCell<F, F>,
+        elem_1: impl Fn(&mut Region<'_, F>) -> Result<AssignedCell<F, F>, Error>,
+        elem_2: impl Fn(&mut Region<'_, F>) -> Result<AssignedCell<F, F>, Error>,
+    ) -> Result<FibonacciCells<F>, Error> {
+        let (elem_1, (elem_2, elem_3)) = self.assign_row(layouter.namespace(|| "init"), name, modulus, elem_1, elem_2)?;
+
+        for (name, cell) in [("elem_1", &elem_1), ("elem_2", &elem_2), ("elem_3", &elem_3)] {
+            self.check_below(layouter.namespace(|| format!("{} below modulus", name)), cell, modulus)?;
+        }
+        Ok((elem_2, elem_3))
+    }
+
+    /// Assigns one more step of the chain, copying the previous `elem_2` and
+    /// `elem_3` into the new row and constraining the new `elem_3` to be
+    /// below `modulus`.
+    pub fn assign(
+        &self,
+        mut layouter: impl Layouter<F>,
+        modulus: &AssignedCell<F, F>,
+        prev: &FibonacciCells<F>,
+    ) -> Result<FibonacciCells<F>, Error> {
+        let config = self.config();
+        let (prev_2, prev_3) = prev;
+
+        let (_, (elem_2, elem_3)) = self.assign_row(
+            layouter.namespace(|| "step"),
+            "steady-state Fibonacci mod m",
+            modulus,
+            |region| prev_2.copy_advice(|| "copy elem_2 into current elem_1", region, config.elem_1, 0),
+            |region| prev_3.copy_advice(|| "copy elem_3 into current elem_2", region, config.elem_2, 0),
+        )?;
+
+        self.check_below(layouter.namespace(|| "elem_3 below modulus"), &elem_3, modulus)?;
+        Ok((elem_2, elem_3))
+    }
+
+    /// Constrains `cell` to equal the given `row` of the instance column.
+    pub fn expose_public(
+        &self,
+        mut layouter: impl Layouter<F>,
+        cell: &AssignedCell<F, F>,
+        row: usize,
+    ) -> Result<(), Error> {
+        layouter.constrain_instance(cell.cell(), self.config().instance, row)
+    }
+}
+
+/// A circuit proving the n-th term of a sequence modulo a public m below
+/// 2^`num_bits`. The instance column holds m in row 0, the n-th term in row 1
+/// and the two seeds in rows 2 and 3.
+#[derive(Clone, Debug)]
+pub struct ModularCircuit<F: FieldExt> {
+    n: usize,
+    num_bits: usize,
+    _marker: PhantomData<F>,
+}
+
+impl<F: FieldExt> ModularCircuit<F> {
+    /// Creates a circuit for the n-th term modulo m, which must have `n >= 2`.
+    pub fn new(n: usize, num_bits: usize) -> Self {
+        assert!(n >= 2, "the circuit has at least one Fibonacci step");
+        Self { n, num_bits, _marker: PhantomData }
+    }
+
+    /// The smallest `k` for which the circuit fits.
+    pub fn k(&self) -> u32 {
+        let mut cs = ConstraintSystem::default();
+        Self::configure(&mut cs);
+        // Each element below m takes two range checks and two rows, and m
+        // itself one range check.
+        let range_check = self.num_bits / K + 1;
+        let rows = (self.n + 1) * (2 + 2 * range_check) + 1 + range_check;
+        min_k(std::cmp::max(rows, 1 << K), &cs)
+    }
+}
+
+impl<F: FieldExt> Circuit<F> for ModularCircuit<F> {
+    type Config = ModularConfig;
+
+    type FloorPlanner = SimpleFloorPlanner;
+
+    fn without_witnesses(&self) -> Self {
+        Self::new(self.n, self.num_bits)
+    }
+
+    fn configure(meta: &mut ConstraintSystem<F>) -> Self::Config {
+        ModularChip::configure(meta)
+    }
+
+    fn synthesize(&self, config: Self::Config, mut layouter: impl Layouter<F>) -> Result<(), Error> {
+        let chip = ModularChip::construct(config, self.num_bits);
+        chip.load_table(layouter.namespace(|| "table"))?;
+
+        let modulus = chip.load_modulus(layouter.namespace(|| "modulus"), 0)?;
+        let mut cells = chip.init_from_instance(layouter.namespace(|| "init"), &modulus, 2, 3)?;
+        for i in 3..=self.n {
+            cells = chip.assign(layouter.namespace(|| format!("x_{}", i)), &modulus, &cells)?;
+        }
+
+        chip.expose_public(layouter.namespace(|| "out"), &cells.1, 1)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use halo2_proofs::{dev::MockProver, pasta::Fp};
+
+    use super::*;
+
+    fn public_inputs(n: usize, elem_1: u64, elem_2: u64, m: u64) -> Vec<Fp> {
+        [m, nth_term(n, elem_1, elem_2, m), elem_1, elem_2].map(Fp::from).to_vec()
+    }
+
+    #[test]
+    fn test_nth_term() {
+        // The Pisano period modulo 10 is 60.
+        for n in 0..60 {
+            assert_eq!(nth_term(n, 0, 1, 10), nth_term(n + 60, 0, 1, 10));
+        }
+        assert_eq!(nth_term(93, 0, 1, u64::MAX), 12200160415121876738);
+    }
+
+    #[test]
+    fn test_modular() {
+        for m in [2, 7, 10, 1_000_000_007, u64::MAX] {
+            for (elem_1, elem_2) in [(0, 1), (2 % m, 1), (m - 1, m - 1)] {
+                let n = 50;
+                let circuit = ModularCircuit::new(n, 64);
+
+                let prover = MockProver::run(circuit.k(), &circuit, vec![public_inputs(n, elem_1, elem_2, m)]).unwrap();
+                prover.assert_satisfied();
+            }
+        }
+    }
+
+    #[test]
+    fn test_wrong_modulus() {
+        let circuit = ModularCircuit::new(20, 64);
+        let mut inputs = public_inputs(20, 0, 1, 7);
+
+        inputs[1] += Fp::one();
+        let prover = MockProver::run(circuit.k(), &circuit, vec![inputs.clone()]).unwrap();
+        assert!(prover.verify().is_err());
+
+        // F(20) mod 7 is not F(20) mod 11.
+        inputs[0] = Fp::from(11);
+        inputs[1] -= Fp::one();
+        let prover = MockProver::run(circuit.k(), &circuit, vec![inputs]).unwrap();
+        assert!(prover.verify().is_err());
+    }
+
+    #[test]
+    fn test_invalid_modulus() {
+        let circuit = ModularCircuit::new(10, 64);
+
+        // Neither 0 nor 2^64, whose low 64 bits are 0, is a modulus.
+        for m in [Fp::zero(), Fp::from_u128(1 << 64)] {
+            assert!(MockProver::run(circuit.k(), &circuit, vec![vec![m, Fp::zero(), Fp::zero(), Fp::one()]]).is_err());
+        }
+    }
+
+    #[test]
+    fn test_seed_not_reduced() {
+        // A seed of m is not below m, even though it is 0 mod m.
+        let circuit = ModularCircuit::new(10, 64);
+        let output = Fp::from(nth_term(10, 0, 1, 7));
+
+        let prover = MockProver::run(circuit.k(), &circuit, vec![vec![Fp::from(7), output, Fp::from(7), Fp::one()]]).unwrap();
+        assert!(prover.verify().is_err());
+    }
+
+    #[test]
+    fn test_public_seeds() {
+        // x_10 = 34 * x_0 + 55 * x_1, so (355, 5) reaches 12345.
+        let circuit = ModularCircuit::new(10, 64);
+        let mut inputs = public_inputs(10, 355, 5, 1_000_000_007);
+        assert_eq!(inputs[1], Fp::from(12345));
+
+        let prover = MockProver::run(circuit.k(), &circuit, vec![inputs.clone()]).unwrap();
+        prover.assert_satisfied();
+
+        // The output cannot be passed off as coming from the seeds 0 and 1.
+        inputs[2] = Fp::zero();
+        inputs[3] = Fp::one();
+        let prover = MockProver::run(circuit.k(), &circuit, vec![inputs]).unwrap();
+        assert!(prover.verify().is_err());
+    }
+}
@@ -0,0 +1,199 @@
+//! Proof generation and verification for [`FibonacciCircuit`] over the Pasta
+//! curves, using the inner-product argument and a Blake2b transcript.
+
+use std::io::{self, Read};
+
+use halo2_proofs::circuit::Value;
+use halo2_proofs::pasta::{EqAffine, Fp};
+use halo2_proofs::plonk::{
+    create_proof, keygen_pk, keygen_vk, verify_proof, Error, ProvingKey, SingleVerifier,
+    VerifyingKey,
+};
+use halo2_proofs::poly::commitment::Params;
+use halo2_proofs::transcript::{Blake2bRead, Blake2bWrite, Challenge255};
+use rand_core::OsRng;
+
+use crate::circuit::{public_inputs, FibonacciCircuit};
+
+/// A proof that the n-th term of the sequence seeded with `seeds` equals
+/// `output`.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Proof {
+    /// The index of the proven term.
+    pub n: usize,
+    /// The seeds x_0 and x_1 the sequence starts from.
+    pub seeds: (Fp, Fp),
+    /// The proven term.
+    pub output: Fp,
+    /// The serialized proof transcript.
+    pub bytes: Vec<u8>,
+}
+
+impl Proof {
+    /// The public inputs this proof is checked against: the output followed
+    /// by the seeds.
+    pub fn public_inputs(&self) -> Vec<Fp> {
+        vec![self.output, self.seeds.0, self.seeds.1]
+    }
+}
+
+/// Generates the IPA parameters for the circuit proving the n-th term, using
+/// the smallest `k` it fits in.
+pub fn setup(n: usize) -> Params<EqAffine> {
+    let circuit = FibonacciCircuit::<Fp>::new(n, Value::unknown(), Value::unknown());
+    Params::new(circuit.k())
+}
+
+/// Returns the `k` that `params` were generated for.
+pub fn params_k(params: &Params<EqAffine>) -> u32 {
+    params.get_g().len().trailing_zeros()
+}
+
+/// Reads parameters written with [`Params::write`], checking that they are
+/// for `k` first.
+///
+/// `Params::read` trusts the `k` header and allocates 2^k points for it, so a
+/// corrupt or hostile file could otherwise make the reader run out of memory.
+pub fn read_params<R: Read>(reader: &mut R, k: u32) -> io::Result<Params<EqAffine>> {
+    let mut header = [0; 4];
+    reader.read_exact(&mut header)?;
+    let found = u32::from_le_bytes(header);
+    if found != k {
+        return Err(io::Error::new(
+            io::ErrorKind::InvalidData,
+            format!("parameters are for k = {}, expected k = {}", found, k),
+        ));
+    }
+
+    Params::read(&mut header.chain(reader))
+}
+
+/// Generates the verifying and proving keys of the circuit for the n-th term.
+///
+/// The keys only depend on `params` and `n`, so the verifier can regenerate
+/// the verifying key instead of receiving it from the prover.
+pub fn keygen(params: &Params<EqAffine>, n: usize) -> Result<ProvingKey<EqAffine>, Error> {
+    let circuit = FibonacciCircuit::<Fp>::new(n, Value::unknown(), Value::unknown());
+
+    let vk = keygen_vk(params, &circuit)?;
+    keygen_pk(params, vk, &circuit)
+}
+
+/// Proves the n-th term of the sequence seeded with `seeds`.
+pub fn prove(
+    params: &Params<EqAffine>,
+    pk: &ProvingKey<EqAffine>,
+    n: usize,
+    seeds: (Fp, Fp),
+) -> Result<Proof, Error> {
+    let circuit = FibonacciCircuit::new(n, Value::known(seeds.0), Value::known(seeds.1));
+    let public_inputs = public_inputs(n, seeds.0, seeds.1);
+
+    let mut transcript = Blake2bWrite::<_, _, Challenge255<_>>::init(vec![]);
+    create_proof(params, pk, &[circuit], &[&[&public_inputs]], OsRng, &mut transcript)?;
+
+    Ok(Proof {
+        n,
+        seeds,
+        output: public_inputs[0],
+        bytes: transcript.finalize(),
+    })
+}
+
+/// Verifies `proof` against `public_inputs`.
+pub fn verify(
+    params: &Params<EqAffine>,
+    vk: &VerifyingKey<EqAffine>,
+    public_inputs: &[Fp],
+    proof: &Proof,
+) -> Result<(), Error> {
+    let strategy = SingleVerifier::new(params);
+    let mut transcript = Blake2bRead::<_, _, Challenge255<_>>::init(&proof.bytes[..]);
+
+    verify_proof(params, vk, strategy, &[&[public_inputs]], &mut transcript)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn setup_keys(n: usize) -> (Params<EqAffine>, ProvingKey<EqAffine>) {
+        let params = setup(n);
+        let pk = keygen(&params, n).unwrap();
+        (params, pk)
+    }
+
+    #[test]
+    fn test_read_params() {
+        let mut bytes = vec![];
+        setup(10).write(&mut bytes).unwrap();
+
+        assert_eq!(params_k(&read_params(&mut &bytes[..], 4).unwrap()), 4);
+
+        // A header claiming a huge k is rejected before anything is allocated.
+        bytes[..4].copy_from_slice(&30u32.to_le_bytes());
+        assert_eq!(read_params(&mut &bytes[..], 4).unwrap_err().kind(), io::ErrorKind::InvalidData);
+    }
+
+    #[test]
+    fn test_prove_and_verify() {
+        let (params, pk) = setup_keys(10);
+
+        let proof = prove(&params, &pk, 10, (Fp::zero(), Fp::one())).unwrap();
+        assert_eq!(proof.output, Fp::from(55));
+
+        verify(&params, pk.get_vk(), &proof.public_inputs(), &proof).unwrap();
+    }
+
+    #[test]
+    fn test_large_n() {
+        let (params, pk) = setup_keys(300);
+
+        let proof = prove(&params, &pk, 300, (Fp::zero(), Fp::one())).unwrap();
+
+        verify(&params, pk.get_vk(), &proof.public_inputs(), &proof).unwrap();
+    }
+
+    #[test]
+    fn test_wrong_public_input() {
+        let (params, pk) = setup_keys(10);
+
+        let proof = prove(&params, &pk, 10, (Fp::zero(), Fp::one())).unwrap();
+
+        assert!(verify(&params, pk.get_vk(), &[Fp::from(56), Fp::zero(), Fp::one()], &proof).is_err());
+    }
+
+    #[test]
+    fn test_wrong_seeds() {
+        let (params, pk) = setup_keys(10);
+
+        // x_10 = 34 * x_0 + 55 * x_1, so these seeds reach 12345.
+        let proof = prove(&params, &pk, 10, (Fp::from(355), Fp::from(5))).unwrap();
+        assert_eq!(proof.output, Fp::from(12345));
+        verify(&params, pk.get_vk(), &proof.public_inputs(), &proof).unwrap();
+
+        // The proof does not claim F(10) = 12345.
+        assert!(verify(&params, pk.get_vk(), &[proof.output, Fp::zero(), Fp::one()], &proof).is_err());
+    }
+
+    #[test]
+    fn test_tampered_proof() {
+        let (params, pk) = setup_keys(10);
+
+        let mut proof = prove(&params, &pk, 10, (Fp::zero(), Fp::one())).unwrap();
+        let mid = proof.bytes.len() / 2;
+        proof.bytes[mid] ^= 1;
+
+        assert!(verify(&params, pk.get_vk(), &proof.public_inputs(), &proof).is_err());
+    }
+
+    #[test]
+    fn test_wrong_verifying_key() {
+        let (params, pk) = setup_keys(10);
+        let other = keygen(&params, 9).unwrap();
+
+        let proof = prove(&params, &pk, 10, (Fp::zero(), Fp::one())).unwrap();
+
+        assert!(verify(&params, other.get_vk(), &proof.public_inputs(), &proof).is_err());
+    }
+}
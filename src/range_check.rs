@@ -0,0 +1,238 @@
+/*
+
+    Checks that a value is below 2^B, for B a multiple of K = 8, by a running
+    sum over its K-bit limbs. For x = 0x0201 and B = 16:
+
+    |   z    | q_lookup
+    -------------------
+    | 0x0201 |    1
+    | 0x0002 |    1
+    | 0x0000 |    0
+
+    z_0 = x,  z_{i+1} = (z_i - limb_i) / 2^K
+
+    q_lookup * (z_cur - 2^K * z_next) in table
+
+    where the table holds 0, ..., 2^K - 1. The last z is constrained to be 0,
+    so x is the sum of B / K limbs of K bits each.
+
+*/
+
+use std::marker::PhantomData;
+
+use halo2_proofs::arithmetic::FieldExt;
+use halo2_proofs::circuit::{AssignedCell, Chip, Layouter, Value};
+use halo2_proofs::plonk::*;
+use halo2_proofs::poly::Rotation;
+
+/// The number of bits in each limb looked up in the table.
+pub const K: usize = 8;
+
+/// Returns `true` if the canonical representative of `value` is below
+/// 2^`num_bits`.
+pub fn fits_in_bits<F: FieldExt>(value: &F, num_bits: usize) -> bool {
+    let repr = value.to_repr();
+    let bytes = repr.as_ref();
+    let (full, partial) = (num_bits / 8, num_bits % 8);
+
+    bytes.iter().skip(full + 1).all(|byte| *byte == 0)
+        && (bytes.get(full).copied().unwrap_or(0) as u16) < 1 << partial
+}
+
+/// The low 64 bits of the canonical representative of `value`.
+pub fn low_u64<F: FieldExt>(value: &F) -> u64 {
+    let mut bytes = [0; 8];
+    bytes.copy_from_slice(&value.to_repr().as_ref()[..8]);
+    u64::from_le_bytes(bytes)
+}
+
+/// Columns, table and selector used by the `range check` lookup.
+#[derive(Clone, Debug, Copy)]
+pub struct RangeCheckConfig {
+    pub z: Column<Advice>,
+    pub table: TableColumn,
+    pub constants: Column<Fixed>,
+    pub q_lookup: Selector,
+}
+
+/// A chip constraining values to a number of bits by looking up K-bit limbs.
+#[derive(Clone, Debug)]
+pub struct RangeCheckChip<F: FieldExt> {
+    config: RangeCheckConfig,
+    _marker: PhantomData<F>,
+}
+
+impl<F: FieldExt> Chip<F> for RangeCheckChip<F> {
+    type Config = RangeCheckConfig;
+    type Loaded = ();
+
+    fn config(&self) -> &Self::Config {
+        &self.config
+    }
+
+    fn loaded(&self) -> &Self::Loaded {
+        &()
+    }
+}
+
+impl<F: FieldExt> RangeCheckChip<F> {
+    /// Constructs a chip from a config returned by
+    /// [`RangeCheckChip::configure`].
+    pub fn construct(config: RangeCheckConfig) -> Self {
+        Self {
+            config,
+            _marker: PhantomData,
+        }
+    }
+
+    /// Allocates an equality-enabled advice column, a table column, a
+    /// constants column and a complex selector, and creates the
+    /// `range check` lookup over them.
+    pub fn configure(cs: &mut ConstraintSystem<F>) -> RangeCheckConfig {
+        let z = cs.advice_column();
+        cs.enable_equality(z);
+        let table = cs.lookup_table_column();
+        let constants = cs.fixed_column();
+        cs.enable_constant(constants);
+        let q_lookup = cs.complex_selector();
+
+        cs.lookup(|virtual_cells| {
+            let q_lookup = virtual_cells.query_selector(q_lookup);
+            let z_cur = virtual_cells.query_advice(z, Rotation::cur());
+            let z_next = virtual_cells.query_advice(z, Rotation::next());
+            let limb = z_cur - z_next * F::from(1 << K);
+
+            //     q_lookup * (z_cur - 2^K * z_next) in table
+            vec![(q_lookup * limb, table)]
+        });
+
+        RangeCheckConfig { z, table, constants, q_lookup }
+    }
+
+    /// Fills the lookup table with 0, ..., 2^K - 1. Must be called once per
+    /// circuit.
+    pub fn load_table(&self, mut layouter: impl Layouter<F>) -> Result<(), Error> {
+        let config = self.config();
+
+        layouter.assign_table(|| "range check table", |mut table| {
+            for value in 0..1 << K {
+                table.assign_cell(|| format!("{}", value), config.table, value, || Value::known(F::from(value as u64)))?;
+            }
+            Ok(())
+        })
+    }
+
+    /// Constrains `cell` to be below 2^`num_bits`, which must be a multiple of
+    /// K.
+    pub fn copy_check(
+        &self,
+        mut layouter: impl Layouter<F>,
+        cell: &AssignedCell<F, F>,
+        num_bits: usize,
+    ) -> Result<(), Error> {
+        let config = self.config();
+        assert_eq!(num_bits % K, 0, "num_bits is a multiple of K");
+
+        layouter.assign_region(|| "range check", |mut region| {
+            let mut z = cell.copy_advice(|| "z_0", &mut region, config.z, 0)?;
+
+            let shift = F::from(1 << K).invert().unwrap();
+            for offset in 0..num_bits / K {
+                // Enable q_lookup
+                config.q_lookup.enable(&mut region, offset)?;
+
+                let z_next = z.value().map(|z| {
+                    let limb = F::from(z.to_repr().as_ref()[0] as u64);
+                    (*z - limb) * shift
+                });
+                z = region.assign_advice(|| format!("z_{}", offset + 1), config.z, offset + 1, || z_next)?;
+            }
+
+            // The value has no bits left
+            region.constrain_constant(z.cell(), F::zero())
+        })
+    }
+
+    /// Witnesses `value` and constrains it to be below 2^`num_bits`, returning
+    /// the cell holding it.
+    pub fn witness_check(
+        &self,
+        mut layouter: impl Layouter<F>,
+        value: Value<F>,
+        num_bits: usize,
+    ) -> Result<AssignedCell<F, F>, Error> {
+        let config = self.config();
+
+        let cell = layouter.assign_region(|| "witness", |mut region| region.assign_advice(|| "value", config.z, 0, || value))?;
+        self.copy_check(layouter.namespace(|| "range check"), &cell, num_bits)?;
+        Ok(cell)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use halo2_proofs::{circuit::SimpleFloorPlanner, dev::MockProver, pasta::Fp};
+
+    use super::*;
+
+    #[derive(Clone, Default)]
+    struct RangeCheckCircuit<const B: usize> {
+        value: Value<Fp>,
+    }
+
+    impl<const B: usize> Circuit<Fp> for RangeCheckCircuit<B> {
+        type Config = RangeCheckConfig;
+
+        type FloorPlanner = SimpleFloorPlanner;
+
+        fn without_witnesses(&self) -> Self {
+            Self::default()
+        }
+
+        fn configure(meta: &mut ConstraintSystem<Fp>) -> Self::Config {
+            RangeCheckChip::configure(meta)
+        }
+
+        fn synthesize(&self, config: Self::Config, mut layouter: impl Layouter<Fp>) -> Result<(), Error> {
+            let chip = RangeCheckChip::construct(config);
+            chip.load_table(layouter.namespace(|| "table"))?;
+
+            chip.witness_check(layouter.namespace(|| "value"), self.value, B)?;
+            Ok(())
+        }
+    }
+
+    #[test]
+    fn test_fits_in_bits() {
+        assert!(fits_in_bits(&Fp::from(255), 8));
+        assert!(!fits_in_bits(&Fp::from(256), 8));
+        assert!(fits_in_bits(&Fp::from(15), 4));
+        assert!(!fits_in_bits(&Fp::from(16), 4));
+        assert!(fits_in_bits(&Fp::from(u64::MAX), 64));
+        assert!(!fits_in_bits(&-Fp::one(), 253));
+    }
+
+    #[test]
+    fn test_low_u64() {
+        assert_eq!(low_u64(&Fp::from(u64::MAX)), u64::MAX);
+        assert_eq!(low_u64(&Fp::from_u128(3 << 64 | 5)), 5);
+    }
+
+    #[test]
+    fn test_range_check() {
+        for value in [0, 1, 255, 256, 0xdead_beef, u64::MAX] {
+            let circuit = RangeCheckCircuit::<64> { value: Value::known(Fp::from(value)) };
+            let prover = MockProver::run(9, &circuit, vec![]).unwrap();
+            prover.assert_satisfied();
+        }
+    }
+
+    #[test]
+    fn test_out_of_range() {
+        for value in [Fp::from(1 << 16), Fp::from(u64::MAX), -Fp::one()] {
+            let circuit = RangeCheckCircuit::<16> { value: Value::known(value) };
+            let prover = MockProver::run(9, &circuit, vec![]).unwrap();
+            assert!(prover.verify().is_err());
+        }
+    }
+}
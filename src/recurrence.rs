@@ -0,0 +1,307 @@
+/*
+
+    x_{n+2} = a * x_{n+1} + b * x_n, e.g. Pell numbers with a = 2, b = 1:
+
+    0, 1, 2, 5, 12, 29, ...
+
+    | elem_1 | elem_2 | elem_3 | coeff_a | coeff_b | q_rec
+    ------------------------------------------------------
+    |    0   |    1   |    2   |    2    |    1    |   1
+    |    1   |    2   |    5   |    2    |    1    |   1
+    |    2   |    5   |   12   |    2    |    1    |   1
+    |        |        |        |         |         |   0
+
+    q_rec * (coeff_a * elem_2 + coeff_b * elem_1 - elem_3) = 0
+
+*/
+
+use halo2_proofs::arithmetic::FieldExt;
+use halo2_proofs::circuit::{AssignedCell, Chip, Layouter, Region, Value};
+use halo2_proofs::plonk::*;
+use halo2_proofs::poly::Rotation;
+
+use crate::fibonacci::FibonacciCells;
+
+/// A second-order linear recurrence `x_{n+2} = a * x_{n+1} + b * x_n` with
+/// its conventional seeds `(x_0, x_1)`.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Recurrence<F: FieldExt> {
+    pub a: F,
+    pub b: F,
+    pub seeds: (F, F),
+}
+
+impl<F: FieldExt> Recurrence<F> {
+    /// 0, 1, 1, 2, 3, 5, ...
+    pub fn fibonacci() -> Self {
+        Self::from_u64(1, 1, (0, 1))
+    }
+
+    /// 2, 1, 3, 4, 7, 11, ...
+    pub fn lucas() -> Self {
+        Self::from_u64(1, 1, (2, 1))
+    }
+
+    /// 0, 1, 2, 5, 12, 29, ...
+    pub fn pell() -> Self {
+        Self::from_u64(2, 1, (0, 1))
+    }
+
+    /// 0, 1, 1, 3, 5, 11, ...
+    pub fn jacobsthal() -> Self {
+        Self::from_u64(1, 2, (0, 1))
+    }
+
+    fn from_u64(a: u64, b: u64, seeds: (u64, u64)) -> Self {
+        Recurrence {
+            a: F::from(a),
+            b: F::from(b),
+            seeds: (F::from(seeds.0), F::from(seeds.1)),
+        }
+    }
+
+    /// Computes `x_n` natively.
+    pub fn nth_term(&self, n: usize) -> F {
+        let (mut x, mut y) = self.seeds;
+        for _ in 0..n {
+            let z = self.a * y + self.b * x;
+            x = y;
+            y = z;
+        }
+        x
+    }
+}
+
+/// Columns and selector used by the `linear recurrence` gate.
+#[derive(Clone, Debug, Copy)]
+pub struct LinearRecurrenceConfig {
+    pub elem_1: Column<Advice>,
+    pub elem_2: Column<Advice>,
+    pub elem_3: Column<Advice>,
+    pub coeff_a: Column<Fixed>,
+    pub coeff_b: Column<Fixed>,
+    pub q_rec: Selector,
+    pub instance: Column<Instance>,
+}
+
+/// A chip proving one step of a [`Recurrence`] per row, with the
+/// coefficients in fixed columns so they are part of the verifying key.
+#[derive(Clone, Debug)]
+pub struct LinearRecurrenceChip<F: FieldExt> {
+    config: LinearRecurrenceConfig,
+    a: F,
+    b: F,
+}
+
+impl<F: FieldExt> Chip<F> for LinearRecurrenceChip<F> {
+    type Config = LinearRecurrenceConfig;
+    type Loaded = ();
+
+    fn config(&self) -> &Self::Config {
+        &self.config
+    }
+
+    fn loaded(&self) -> &Self::Loaded {
+        &()
+    }
+}
+
+impl<F: FieldExt> LinearRecurrenceChip<F> {
+    /// Constructs a chip assigning the coefficients `a` and `b` of
+    /// `recurrence` to every row it uses.
+    pub fn construct(config: LinearRecurrenceConfig, recurrence: &Recurrence<F>) -> Self {
+        Self {
+            config,
+            a: recurrence.a,
+            b: recurrence.b,
+        }
+    }
+
+    /// Allocates three equality-enabled advice columns, two fixed coefficient
+    /// columns, a selector and an instance column, and creates the
+    /// `linear recurrence` gate over them.
+    pub fn configure(cs: &mut ConstraintSystem<F>) -> LinearRecurrenceConfig {
+        let elem_1 = cs.advice_column();
+        cs.enable_equality(elem_1);
+        let elem_2 = cs.advice_column();
+        cs.enable_equality(elem_2);
+        let elem_3 = cs.advice_column();
+        cs.enable_equality(elem_3);
+        let coeff_a = cs.fixed_column();
+        let coeff_b = cs.fixed_column();
+        let q_rec = cs.selector();
+        let instance = cs.instance_column();
+        cs.enable_equality(instance);
+
+        cs.create_gate("linear recurrence", |virtual_cells| {
+            let q_rec = virtual_cells.query_selector(q_rec);
+            let elem_1 = virtual_cells.query_advice(elem_1, Rotation::cur());
+            let elem_2 = virtual_cells.query_advice(elem_2, Rotation::cur());
+            let elem_3 = virtual_cells.query_advice(elem_3, Rotation::cur());
+            let coeff_a = virtual_cells.query_fixed(coeff_a, Rotation::cur());
+            let coeff_b = virtual_cells.query_fixed(coeff_b, Rotation::cur());
+
+            vec![
+                //     q_rec * (coeff_a * elem_2 + coeff_b * elem_1 - elem_3) = 0
+                q_rec * (coeff_a * elem_2 + coeff_b * elem_1 - elem_3),
+            ]
+        });
+
+        LinearRecurrenceConfig { elem_1, elem_2, elem_3, coeff_a, coeff_b, q_rec, instance }
+    }
+
+    /// Enables the gate at `offset` and assigns the coefficients there.
+    fn enable(&self, region: &mut Region<'_, F>, offset: usize) -> Result<(), Error> {
+        let config = self.config();
+
+        // Enable q_rec
+        config.q_rec.enable(region, offset)?;
+
+        // Assign the coefficients
+        region.assign_fixed(|| "coeff_a", config.coeff_a, offset, || Value::known(self.a))?;
+        region.assign_fixed(|| "coeff_b", config.coeff_b, offset, || Value::known(self.b))?;
+
+        Ok(())
+    }
+
+    /// Assigns the first row of the sequence from the seeds `elem_1` and
+    /// `elem_2`, returning the cells holding `elem_2` and `elem_3`.
+    pub fn init(
+        &self,
+        mut layouter: impl Layouter<F>,
+        elem_1: Value<F>,
+        elem_2: Value<F>,
+    ) -> Result<FibonacciCells<F>, Error> {
+        let config = self.config();
+
+        layouter.assign_region(|| "init recurrence", |mut region| {
+            let offset = 0;
+            self.enable(&mut region, offset)?;
+
+            // Assign elem_1
+            region.assign_advice(|| "elem_1", config.elem_1, offset, || elem_1)?;
+
+            // Assign elem_2
+            let elem_2 = region.assign_advice(|| "elem_2", config.elem_2, offset, || elem_2)?;
+
+            let elem_3 = elem_2.value().map(|elem_2| self.a * elem_2) + elem_1.map(|elem_1| self.b * elem_1);
+            // Assign elem_3
+            let elem_3 = region.assign_advice(|| "elem_3", config.elem_3, offset, || elem_3)?;
+
+            Ok((elem_2, elem_3))
+        })
+    }
+
+    /// Assigns one more step of the sequence, copying the previous `elem_2`
+    /// and `elem_3` into the new row and returning the new `(elem_2, elem_3)`.
+    pub fn assign(
+        &self,
+        mut layouter: impl Layouter<F>,
+        elem_2: AssignedCell<F, F>,
+        elem_3: AssignedCell<F, F>,
+    ) -> Result<FibonacciCells<F>, Error> {
+        let config = self.config();
+
+        layouter.assign_region(|| "steady-state recurrence", |mut region| {
+            let offset = 0;
+            self.enable(&mut region, offset)?;
+
+            // Copy elem_1 (which is the previous elem_2)
+            let elem_1 = elem_2.copy_advice(|| "copy elem_2 into current elem_1", &mut region, config.elem_1, offset)?;
+
+            // Copy elem_2 (which is the previous elem_3)
+            let elem_2 = elem_3.copy_advice(|| "copy elem_3 into current elem_2", &mut region, config.elem_2, offset)?;
+
+            let elem_3 = elem_2.value().map(|elem_2| self.a * elem_2) + elem_1.value().map(|elem_1| self.b * elem_1);
+            // Assign elem_3
+            let elem_3 = region.assign_advice(|| "elem_3", config.elem_3, offset, || elem_3)?;
+
+            Ok((elem_2, elem_3))
+        })
+    }
+
+    /// Constrains `cell` to equal the given `row` of the instance column.
+    pub fn expose_public(
+        &self,
+        mut layouter: impl Layouter<F>,
+        cell: &AssignedCell<F, F>,
+        row: usize,
+    ) -> Result<(), Error> {
+        layouter.constrain_instance(cell.cell(), self.config().instance, row)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use halo2_proofs::{circuit::SimpleFloorPlanner, dev::MockProver, pasta::Fp};
+
+    use super::*;
+
+    #[derive(Clone)]
+    struct RecurrenceCircuit {
+        recurrence: Recurrence<Fp>,
+        n: usize,
+    }
+
+    impl Circuit<Fp> for RecurrenceCircuit {
+        type Config = LinearRecurrenceConfig;
+
+        type FloorPlanner = SimpleFloorPlanner;
+
+        fn without_witnesses(&self) -> Self {
+            self.clone()
+        }
+
+        fn configure(meta: &mut ConstraintSystem<Fp>) -> Self::Config {
+            LinearRecurrenceChip::configure(meta)
+        }
+
+        fn synthesize(&self, config: Self::Config, mut layouter: impl Layouter<Fp>) -> Result<(), Error> {
+            let chip = LinearRecurrenceChip::construct(config, &self.recurrence);
+            let (elem_1, elem_2) = self.recurrence.seeds;
+
+            let (mut elem_2, mut elem_3) =
+                chip.init(layouter.namespace(|| "init"), Value::known(elem_1), Value::known(elem_2))?;
+            for i in 3..=self.n {
+                (elem_2, elem_3) = chip.assign(layouter.namespace(|| format!("x_{}", i)), elem_2, elem_3)?;
+            }
+
+            chip.expose_public(layouter.namespace(|| "out"), &elem_3, 0)
+        }
+    }
+
+    #[test]
+    fn test_sequences() {
+        for (recurrence, x_10) in [
+            (Recurrence::fibonacci(), 55),
+            (Recurrence::lucas(), 123),
+            (Recurrence::pell(), 2378),
+            (Recurrence::jacobsthal(), 341),
+        ] {
+            assert_eq!(recurrence.nth_term(10), Fp::from(x_10));
+
+            let circuit = RecurrenceCircuit { recurrence, n: 10 };
+            let prover = MockProver::run(4, &circuit, vec![vec![Fp::from(x_10)]]).unwrap();
+            prover.assert_satisfied();
+        }
+    }
+
+    #[test]
+    fn test_matches_fibonacci_chip() {
+        let recurrence = Recurrence::<Fp>::fibonacci();
+        for n in 2..20 {
+            assert_eq!(
+                recurrence.nth_term(n),
+                crate::circuit::nth_term(n, Fp::zero(), Fp::one())
+            );
+        }
+    }
+
+    #[test]
+    fn test_wrong_sequence() {
+        // A Pell chain does not prove the 10th Fibonacci number.
+        let circuit = RecurrenceCircuit { recurrence: Recurrence::pell(), n: 10 };
+        let prover = MockProver::run(4, &circuit, vec![vec![Fp::from(55)]]).unwrap();
+        assert!(prover.verify().is_err());
+    }
+}
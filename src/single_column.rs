@@ -0,0 +1,192 @@
+/*
+
+    The whole sequence in one advice column, with the gate reaching the next
+    two rows instead of copying cells between regions:
+
+    | value | q_fib
+    ---------------
+    |  x_0  |   1
+    |  x_1  |   1
+    |  x_2  |   1
+    |  ...  |  ...
+    |x_{n-2}|   1
+    |x_{n-1}|   0
+    |  x_n  |   0
+
+    q_fib * (value(cur) + value(next) - value(next + 1)) = 0
+
+    Only the seeds and the output take part in the permutation argument.
+
+*/
+
+use std::marker::PhantomData;
+
+use halo2_proofs::arithmetic::FieldExt;
+use halo2_proofs::circuit::{AssignedCell, Chip, Layouter, Value};
+use halo2_proofs::plonk::*;
+use halo2_proofs::poly::Rotation;
+
+use crate::circuit::{FibonacciLayout, LayoutCircuit};
+use crate::fibonacci::ChainCells;
+
+/// Columns and selector used by the `single-column fibonacci` gate.
+#[derive(Clone, Debug, Copy)]
+pub struct SingleColumnConfig {
+    pub value: Column<Advice>,
+    pub q_fib: Selector,
+    pub instance: Column<Instance>,
+}
+
+/// A chip laying out a Fibonacci chain in a single advice column.
+#[derive(Clone, Debug)]
+pub struct SingleColumnChip<F: FieldExt> {
+    config: SingleColumnConfig,
+    _marker: PhantomData<F>,
+}
+
+impl<F: FieldExt> Chip<F> for SingleColumnChip<F> {
+    type Config = SingleColumnConfig;
+    type Loaded = ();
+
+    fn config(&self) -> &Self::Config {
+        &self.config
+    }
+
+    fn loaded(&self) -> &Self::Loaded {
+        &()
+    }
+}
+
+impl<F: FieldExt> SingleColumnChip<F> {
+    /// Constructs a chip from a config returned by
+    /// [`SingleColumnChip::configure`].
+    pub fn construct(config: SingleColumnConfig) -> Self {
+        Self {
+            config,
+            _marker: PhantomData,
+        }
+    }
+
+    /// Allocates one advice column, a selector and an instance column, and
+    /// creates the `single-column fibonacci` gate over them.
+    pub fn configure(cs: &mut ConstraintSystem<F>) -> SingleColumnConfig {
+        let value = cs.advice_column();
+        cs.enable_equality(value);
+        let q_fib = cs.selector();
+        let instance = cs.instance_column();
+        cs.enable_equality(instance);
+
+        cs.create_gate("single-column fibonacci", |virtual_cells| {
+            let q_fib = virtual_cells.query_selector(q_fib);
+            let cur = virtual_cells.query_advice(value, Rotation::cur());
+            let next = virtual_cells.query_advice(value, Rotation::next());
+            let next_next = virtual_cells.query_advice(value, Rotation(2));
+
+            vec![
+                //     q_fib * (value(cur) + value(next) - value(next + 1)) = 0
+                q_fib * (cur + next - next_next),
+            ]
+        });
+
+        SingleColumnConfig { value, q_fib, instance }
+    }
+
+    /// Assigns x_0 to x_n of the sequence seeded with `elem_1` and `elem_2`
+    /// in one region, returning the cells holding x_0, x_1 and x_n.
+    pub fn assign_chain(
+        &self,
+        mut layouter: impl Layouter<F>,
+        n: usize,
+        elem_1: Value<F>,
+        elem_2: Value<F>,
+    ) -> Result<ChainCells<F>, Error> {
+        let config = self.config();
+        assert!(n >= 2, "the chain has at least one Fibonacci step");
+
+        layouter.assign_region(|| "single-column Fibonacci", |mut region| {
+            // Assign the seeds
+            let seed_1 = region.assign_advice(|| "x_0", config.value, 0, || elem_1)?;
+            let seed_2 = region.assign_advice(|| "x_1", config.value, 1, || elem_2)?;
+            let (mut prev, mut cur) = (seed_1.clone(), seed_2.clone());
+
+            for offset in 2..=n {
+                // Enable q_fib on the row of x_{offset - 2}
+                config.q_fib.enable(&mut region, offset - 2)?;
+
+                let next = prev.value().copied() + cur.value();
+                prev = cur;
+                cur = region.assign_advice(|| format!("x_{}", offset), config.value, offset, || next)?;
+            }
+
+            Ok((seed_1, seed_2, cur))
+        })
+    }
+
+    /// Constrains `cell` to equal the given `row` of the instance column.
+    pub fn expose_public(
+        &self,
+        mut layouter: impl Layouter<F>,
+        cell: &AssignedCell<F, F>,
+        row: usize,
+    ) -> Result<(), Error> {
+        layouter.constrain_instance(cell.cell(), self.config().instance, row)
+    }
+}
+
+impl<F: FieldExt> FibonacciLayout<F> for SingleColumnChip<F> {
+    fn configure(meta: &mut ConstraintSystem<F>) -> SingleColumnConfig {
+        SingleColumnChip::configure(meta)
+    }
+
+    fn construct(config: SingleColumnConfig) -> Self {
+        SingleColumnChip::construct(config)
+    }
+
+    fn rows(n: usize) -> usize {
+        n + 1
+    }
+
+    fn assign_nth_term(
+        &self,
+        layouter: impl Layouter<F>,
+        n: usize,
+        elem_1: Value<F>,
+        elem_2: Value<F>,
+    ) -> Result<ChainCells<F>, Error> {
+        self.assign_chain(layouter, n, elem_1, elem_2)
+    }
+
+    fn expose_public(&self, layouter: impl Layouter<F>, cell: &AssignedCell<F, F>, row: usize) -> Result<(), Error> {
+        SingleColumnChip::expose_public(self, layouter, cell, row)
+    }
+}
+
+/// The single-column counterpart of [`crate::circuit::FibonacciCircuit`].
+pub type SingleColumnCircuit<F> = LayoutCircuit<F, SingleColumnChip<F>>;
+
+#[cfg(test)]
+mod tests {
+    use halo2_proofs::{dev::MockProver, pasta::Fp};
+
+    use super::*;
+    use crate::circuit::public_inputs;
+
+    #[test]
+    fn test_single_column() {
+        for n in 2..=100 {
+            let circuit = SingleColumnCircuit::new(n, Value::known(Fp::zero()), Value::known(Fp::one()));
+            let inputs = public_inputs(n, Fp::zero(), Fp::one());
+
+            let prover = MockProver::run(circuit.k(), &circuit, vec![inputs]).unwrap();
+            prover.assert_satisfied();
+        }
+    }
+
+    #[test]
+    fn test_wrong_output() {
+        let circuit = SingleColumnCircuit::new(10, Value::known(Fp::zero()), Value::known(Fp::one()));
+
+        let prover = MockProver::run(circuit.k(), &circuit, vec![vec![Fp::from(56), Fp::zero(), Fp::one()]]).unwrap();
+        assert!(prover.verify().is_err());
+    }
+}
@@ -0,0 +1,228 @@
+/*
+
+    Two consecutive terms per row, with the gate constraining the next row
+    instead of copying cells into it:
+
+    |  elem_1 |  elem_2 | q_fib
+    ---------------------------
+    |   x_0   |   x_1   |   1
+    |   x_1   |   x_2   |   1
+    |   ...   |   ...   |  ...
+    | x_{n-2} | x_{n-1} |   1
+    | x_{n-1} |   x_n   |   0
+
+    q_fib * (elem_1(next) - elem_2(cur)) = 0
+    q_fib * (elem_2(next) - elem_1(cur) - elem_2(cur)) = 0
+
+    Only the seeds in the first row and x_n in the last take part in the
+    permutation argument.
+
+*/
+
+use std::marker::PhantomData;
+
+use halo2_proofs::arithmetic::FieldExt;
+use halo2_proofs::circuit::{AssignedCell, Chip, Layouter, Value};
+use halo2_proofs::plonk::*;
+use halo2_proofs::poly::Rotation;
+
+use crate::circuit::{FibonacciLayout, LayoutCircuit};
+use crate::fibonacci::ChainCells;
+
+/// Columns and selector used by the `two-column fibonacci` gate.
+#[derive(Clone, Debug, Copy)]
+pub struct TwoColumnConfig {
+    pub elem_1: Column<Advice>,
+    pub elem_2: Column<Advice>,
+    pub q_fib: Selector,
+    pub instance: Column<Instance>,
+}
+
+/// A chip laying out a Fibonacci chain in two advice columns.
+#[derive(Clone, Debug)]
+pub struct TwoColumnChip<F: FieldExt> {
+    config: TwoColumnConfig,
+    _marker: PhantomData<F>,
+}
+
+impl<F: FieldExt> Chip<F> for TwoColumnChip<F> {
+    type Config = TwoColumnConfig;
+    type Loaded = ();
+
+    fn config(&self) -> &Self::Config {
+        &self.config
+    }
+
+    fn loaded(&self) -> &Self::Loaded {
+        &()
+    }
+}
+
+impl<F: FieldExt> TwoColumnChip<F> {
+    /// Constructs a chip from a config returned by
+    /// [`TwoColumnChip::configure`].
+    pub fn construct(config: TwoColumnConfig) -> Self {
+        Self {
+            config,
+            _marker: PhantomData,
+        }
+    }
+
+    /// Allocates two equality-enabled advice columns, a selector and an
+    /// instance column, and creates the `two-column fibonacci` gate over them.
+    pub fn configure(cs: &mut ConstraintSystem<F>) -> TwoColumnConfig {
+        let elem_1 = cs.advice_column();
+        cs.enable_equality(elem_1);
+        let elem_2 = cs.advice_column();
+        cs.enable_equality(elem_2);
+        let q_fib = cs.selector();
+        let instance = cs.instance_column();
+        cs.enable_equality(instance);
+
+        cs.create_gate("two-column fibonacci", |virtual_cells| {
+            let q_fib = virtual_cells.query_selector(q_fib);
+            let elem_1_cur = virtual_cells.query_advice(elem_1, Rotation::cur());
+            let elem_2_cur = virtual_cells.query_advice(elem_2, Rotation::cur());
+            let elem_1_next = virtual_cells.query_advice(elem_1, Rotation::next());
+            let elem_2_next = virtual_cells.query_advice(elem_2, Rotation::next());
+
+            Constraints::with_selector(
+                q_fib,
+                [
+                    ("shift", elem_1_next - elem_2_cur.clone()),
+                    ("sum", elem_2_next - elem_1_cur - elem_2_cur),
+                ],
+            )
+        });
+
+        TwoColumnConfig { elem_1, elem_2, q_fib, instance }
+    }
+
+    /// Assigns x_0 to x_n of the sequence seeded with `elem_1` and `elem_2`
+    /// in one region, returning the cells holding x_0, x_1 and x_n.
+    pub fn assign_chain(
+        &self,
+        mut layouter: impl Layouter<F>,
+        n: usize,
+        elem_1: Value<F>,
+        elem_2: Value<F>,
+    ) -> Result<ChainCells<F>, Error> {
+        let config = self.config();
+        assert!(n >= 2, "the chain has at least one Fibonacci step");
+
+        layouter.assign_region(|| "two-column Fibonacci", |mut region| {
+            // Assign the seeds
+            let seed_1 = region.assign_advice(|| "x_0", config.elem_1, 0, || elem_1)?;
+            let seed_2 = region.assign_advice(|| "x_1", config.elem_2, 0, || elem_2)?;
+            let (mut prev, mut cur) = (seed_1.clone(), seed_2.clone());
+
+            for offset in 1..n {
+                // Enable q_fib on the previous row
+                config.q_fib.enable(&mut region, offset - 1)?;
+
+                let next = prev.value().copied() + cur.value();
+                prev = region.assign_advice(|| format!("x_{}", offset), config.elem_1, offset, || cur.value().copied())?;
+                cur = region.assign_advice(|| format!("x_{}", offset + 1), config.elem_2, offset, || next)?;
+            }
+
+            Ok((seed_1, seed_2, cur))
+        })
+    }
+
+    /// Constrains `cell` to equal the given `row` of the instance column.
+    pub fn expose_public(
+        &self,
+        mut layouter: impl Layouter<F>,
+        cell: &AssignedCell<F, F>,
+        row: usize,
+    ) -> Result<(), Error> {
+        layouter.constrain_instance(cell.cell(), self.config().instance, row)
+    }
+}
+
+impl<F: FieldExt> FibonacciLayout<F> for TwoColumnChip<F> {
+    fn configure(meta: &mut ConstraintSystem<F>) -> TwoColumnConfig {
+        TwoColumnChip::configure(meta)
+    }
+
+    fn construct(config: TwoColumnConfig) -> Self {
+        TwoColumnChip::construct(config)
+    }
+
+    fn rows(n: usize) -> usize {
+        n
+    }
+
+    fn assign_nth_term(
+        &self,
+        layouter: impl Layouter<F>,
+        n: usize,
+        elem_1: Value<F>,
+        elem_2: Value<F>,
+    ) -> Result<ChainCells<F>, Error> {
+        self.assign_chain(layouter, n, elem_1, elem_2)
+    }
+
+    fn expose_public(&self, layouter: impl Layouter<F>, cell: &AssignedCell<F, F>, row: usize) -> Result<(), Error> {
+        TwoColumnChip::expose_public(self, layouter, cell, row)
+    }
+}
+
+/// The two-column counterpart of [`crate::circuit::FibonacciCircuit`].
+pub type TwoColumnCircuit<F> = LayoutCircuit<F, TwoColumnChip<F>>;
+
+#[cfg(test)]
+mod tests {
+    use halo2_proofs::{dev::MockProver, pasta::Fp};
+
+    use super::*;
+    use crate::circuit::{public_inputs, FibonacciCircuit};
+    use crate::single_column::SingleColumnCircuit;
+
+    #[test]
+    fn test_two_column() {
+        for n in 2..=100 {
+            let circuit = TwoColumnCircuit::new(n, Value::known(Fp::zero()), Value::known(Fp::one()));
+            let inputs = public_inputs(n, Fp::zero(), Fp::one());
+
+            let prover = MockProver::run(circuit.k(), &circuit, vec![inputs]).unwrap();
+            prover.assert_satisfied();
+        }
+    }
+
+    #[test]
+    fn test_wrong_output() {
+        let circuit = TwoColumnCircuit::new(10, Value::known(Fp::zero()), Value::known(Fp::one()));
+
+        let prover = MockProver::run(circuit.k(), &circuit, vec![vec![Fp::from(56), Fp::zero(), Fp::one()]]).unwrap();
+        assert!(prover.verify().is_err());
+    }
+
+    #[test]
+    fn test_layouts_agree() {
+        fn check<L: FibonacciLayout<Fp>>(n: usize) {
+            let circuit = LayoutCircuit::<Fp, L>::new(n, Value::known(Fp::from(2)), Value::known(Fp::one()));
+            let inputs = public_inputs(n, Fp::from(2), Fp::one());
+
+            let prover = MockProver::run(circuit.k(), &circuit, vec![inputs]).unwrap();
+            prover.assert_satisfied();
+        }
+
+        for n in [2, 3, 50] {
+            check::<crate::fibonacci::FibonacciChip<Fp>>(n);
+            check::<TwoColumnChip<Fp>>(n);
+            check::<crate::single_column::SingleColumnChip<Fp>>(n);
+        }
+
+        // The rotation layouts trade an extra row or two for fewer columns and
+        // copies.
+        let rows = |n| {
+            (
+                FibonacciCircuit::<Fp>::new(n, Value::unknown(), Value::unknown()).rows(),
+                TwoColumnCircuit::<Fp>::new(n, Value::unknown(), Value::unknown()).rows(),
+                SingleColumnCircuit::<Fp>::new(n, Value::unknown(), Value::unknown()).rows(),
+            )
+        };
+        assert_eq!(rows(100), (99, 100, 101));
+    }
+}
@@ -0,0 +1,270 @@
+/*
+
+    W Fibonacci steps per row, in 2 + W advice columns. For W = 3:
+
+    | elem_1 | elem_2 | elem_3 | elem_4 | elem_5 | q_fib
+    ---------------------------------------------------
+    |   x_0  |   x_1  |   x_2  |   x_3  |   x_4  |   1
+    |   x_3  |   x_4  |   x_5  |   x_6  |   x_7  |   1
+    |   x_6  |   x_7  |   x_8  |   x_9  |  x_10  |   1
+    |        |        |        |        |        |   0
+
+    q_fib * (elem_i + elem_{i+1} - elem_{i+2}) = 0    for i = 1, ..., W
+
+    Each row copies the last two cells of the previous row into its first two
+    columns, so W = 1 is the layout of `FibonacciChip`. The last row may run
+    past x_n, in which case x_n is taken from the middle of the row.
+
+*/
+
+use std::marker::PhantomData;
+
+use halo2_proofs::arithmetic::FieldExt;
+use halo2_proofs::circuit::{AssignedCell, Chip, Layouter, Region, Value};
+use halo2_proofs::plonk::*;
+use halo2_proofs::poly::Rotation;
+
+use crate::circuit::{FibonacciLayout, LayoutCircuit};
+use crate::fibonacci::ChainCells;
+
+/// Columns and selector used by the `wide fibonacci` gate.
+#[derive(Clone, Debug)]
+pub struct WideConfig {
+    /// The two seed columns followed by one column per step.
+    pub elems: Vec<Column<Advice>>,
+    pub q_fib: Selector,
+    pub instance: Column<Instance>,
+}
+
+impl WideConfig {
+    /// The number of Fibonacci steps per row.
+    pub fn width(&self) -> usize {
+        self.elems.len() - 2
+    }
+}
+
+/// A chip proving `W` Fibonacci steps per row.
+#[derive(Clone, Debug)]
+pub struct WideChip<F: FieldExt, const W: usize> {
+    config: WideConfig,
+    _marker: PhantomData<F>,
+}
+
+impl<F: FieldExt, const W: usize> Chip<F> for WideChip<F, W> {
+    type Config = WideConfig;
+    type Loaded = ();
+
+    fn config(&self) -> &Self::Config {
+        &self.config
+    }
+
+    fn loaded(&self) -> &Self::Loaded {
+        &()
+    }
+}
+
+impl<F: FieldExt, const W: usize> WideChip<F, W> {
+    /// Constructs a chip from a config returned by [`WideChip::configure`].
+    pub fn construct(config: WideConfig) -> Self {
+        assert_eq!(config.width(), W, "config has a different width");
+        Self {
+            config,
+            _marker: PhantomData,
+        }
+    }
+
+    /// Allocates `2 + W` equality-enabled advice columns, a selector and an
+    /// instance column, and creates the `wide fibonacci` gate over them.
+    pub fn configure(cs: &mut ConstraintSystem<F>) -> WideConfig {
+        assert!(W >= 1, "each row has at least one Fibonacci step");
+
+        let elems: Vec<_> = (0..W + 2)
+            .map(|_| {
+                let elem = cs.advice_column();
+                cs.enable_equality(elem);
+                elem
+            })
+            .collect();
+        let q_fib = cs.selector();
+        let instance = cs.instance_column();
+        cs.enable_equality(instance);
+
+        cs.create_gate("wide fibonacci", |virtual_cells| {
+            let q_fib = virtual_cells.query_selector(q_fib);
+            let elems: Vec<_> = elems
+                .iter()
+                .map(|elem| virtual_cells.query_advice(*elem, Rotation::cur()))
+                .collect();
+
+            Constraints::with_selector(
+                q_fib,
+                elems
+                    .windows(3)
+                    //     q_fib * (elem_i + elem_{i+1} - elem_{i+2}) = 0
+                    .map(|window| window[0].clone() + window[1].clone() - window[2].clone())
+                    .collect::<Vec<_>>(),
+            )
+        });
+
+        WideConfig { elems, q_fib, instance }
+    }
+
+    /// Assigns the first row of the chain from the two seeds `elem_1` and
+    /// `elem_2`, returning every cell of the row.
+    pub fn init(
+        &self,
+        mut layouter: impl Layouter<F>,
+        elem_1: Value<F>,
+        elem_2: Value<F>,
+    ) -> Result<Vec<AssignedCell<F, F>>, Error> {
+        let config = self.config();
+
+        layouter.assign_region(|| "init wide Fibonacci", |mut region| {
+            let offset = 0;
+
+            // Enable q_fib
+            config.q_fib.enable(&mut region, offset)?;
+
+            // Assign the seeds
+            let elem_1 = region.assign_advice(|| "elem_1", config.elems[0], offset, || elem_1)?;
+            let elem_2 = region.assign_advice(|| "elem_2", config.elems[1], offset, || elem_2)?;
+
+            self.assign_steps(&mut region, vec![elem_1, elem_2])
+        })
+    }
+
+    /// Assigns W more steps of the chain, copying the last two cells of the
+    /// previous row into the new row and returning every cell of the row.
+    pub fn assign(
+        &self,
+        mut layouter: impl Layouter<F>,
+        prev: &[AssignedCell<F, F>],
+    ) -> Result<Vec<AssignedCell<F, F>>, Error> {
+        let config = self.config();
+        let (elem_1, elem_2) = match prev {
+            [.., elem_1, elem_2] => (elem_1, elem_2),
+            _ => panic!("expected the cells of the previous row"),
+        };
+
+        layouter.assign_region(|| "steady-state wide Fibonacci", |mut region| {
+            let offset = 0;
+
+            // Enable q_fib
+            config.q_fib.enable(&mut region, offset)?;
+
+            // Copy the last two cells of the previous row
+            let elem_1 = elem_1.copy_advice(|| "copy into elem_1", &mut region, config.elems[0], offset)?;
+            let elem_2 = elem_2.copy_advice(|| "copy into elem_2", &mut region, config.elems[1], offset)?;
+
+            self.assign_steps(&mut region, vec![elem_1, elem_2])
+        })
+    }
+
+    /// Assigns the W steps following the two cells in `row`.
+    fn assign_steps(
+        &self,
+        region: &mut Region<'_, F>,
+        mut row: Vec<AssignedCell<F, F>>,
+    ) -> Result<Vec<AssignedCell<F, F>>, Error> {
+        for (i, column) in self.config().elems.iter().enumerate().skip(2) {
+            let value = row[i - 2].value().copied() + row[i - 1].value();
+            row.push(region.assign_advice(|| format!("elem_{}", i + 1), *column, 0, || value)?);
+        }
+        Ok(row)
+    }
+
+    /// Constrains `cell` to equal the given `row` of the instance column.
+    pub fn expose_public(
+        &self,
+        mut layouter: impl Layouter<F>,
+        cell: &AssignedCell<F, F>,
+        row: usize,
+    ) -> Result<(), Error> {
+        layouter.constrain_instance(cell.cell(), self.config().instance, row)
+    }
+}
+
+impl<F: FieldExt, const W: usize> FibonacciLayout<F> for WideChip<F, W> {
+    fn configure(meta: &mut ConstraintSystem<F>) -> WideConfig {
+        WideChip::<F, W>::configure(meta)
+    }
+
+    fn construct(config: WideConfig) -> Self {
+        WideChip::construct(config)
+    }
+
+    fn rows(n: usize) -> usize {
+        (n - 2) / W + 1
+    }
+
+    fn assign_nth_term(
+        &self,
+        mut layouter: impl Layouter<F>,
+        n: usize,
+        elem_1: Value<F>,
+        elem_2: Value<F>,
+    ) -> Result<ChainCells<F>, Error> {
+        // Row r holds x_{rW} to x_{rW + W + 1}.
+        let last = (n - 2) / W;
+
+        let mut row = self.init(layouter.namespace(|| "init"), elem_1, elem_2)?;
+        let (seed_1, seed_2) = (row[0].clone(), row[1].clone());
+        for r in 1..=last {
+            row = self.assign(layouter.namespace(|| format!("row {}", r)), &row)?;
+        }
+        Ok((seed_1, seed_2, row.swap_remove(n - last * W)))
+    }
+
+    fn expose_public(&self, layouter: impl Layouter<F>, cell: &AssignedCell<F, F>, row: usize) -> Result<(), Error> {
+        WideChip::expose_public(self, layouter, cell, row)
+    }
+}
+
+/// The circuit with `W` Fibonacci steps per row.
+pub type WideCircuit<F, const W: usize> = LayoutCircuit<F, WideChip<F, W>>;
+
+#[cfg(test)]
+mod tests {
+    use halo2_proofs::{dev::MockProver, pasta::Fp};
+
+    use super::*;
+    use crate::circuit::public_inputs;
+
+    #[test]
+    fn test_many_widths() {
+        fn check<const W: usize>() {
+            for n in 2..=40 {
+                let circuit = WideCircuit::<Fp, W>::new(n, Value::known(Fp::zero()), Value::known(Fp::one()));
+                let inputs = public_inputs(n, Fp::zero(), Fp::one());
+
+                let prover = MockProver::run(circuit.k(), &circuit, vec![inputs]).unwrap();
+                prover.assert_satisfied();
+            }
+        }
+
+        check::<1>();
+        check::<2>();
+        check::<3>();
+        check::<8>();
+    }
+
+    #[test]
+    fn test_wrong_output() {
+        let circuit = WideCircuit::<Fp, 4>::new(10, Value::known(Fp::zero()), Value::known(Fp::one()));
+
+        let prover = MockProver::run(circuit.k(), &circuit, vec![vec![Fp::from(56), Fp::zero(), Fp::one()]]).unwrap();
+        assert!(prover.verify().is_err());
+    }
+
+    #[test]
+    fn test_rows() {
+        // x_2 .. x_10 is 9 steps, or 3 rows of 3.
+        assert_eq!(WideCircuit::<Fp, 3>::new(10, Value::unknown(), Value::unknown()).rows(), 3);
+        assert_eq!(WideCircuit::<Fp, 3>::new(11, Value::unknown(), Value::unknown()).rows(), 4);
+
+        // A wider row fits a longer chain into the same k.
+        let k = |circuit: &WideCircuit<Fp, 16>| circuit.k();
+        let narrow = crate::circuit::FibonacciCircuit::<Fp>::new(100, Value::unknown(), Value::unknown());
+        assert_eq!(k(&WideCircuit::new(1600, Value::unknown(), Value::unknown())), narrow.k());
+    }
+}
@@ -0,0 +1,341 @@
+/*
+
+    Fibonacci with u64::wrapping_add, e.g. from F(92) and F(93):
+
+    |        elem_1        |        elem_2        |        elem_3        | carry | q_wrap
+    -------------------------------------------------------------------------------------
+    | 7540113804746346429  | 12200160415121876738 | 1293530146158671551  |   1   |   1
+
+    q_wrap * carry * (1 - carry) = 0
+    q_wrap * (elem_1 + elem_2 - elem_3 - carry * 2^64) = 0
+
+    Every elem is range-checked below 2^64, so elem_1 + elem_2 < 2^65 and the
+    gate holds over the integers: elem_3 is the low 64 bits of the sum and
+    carry its 65th bit.
+
+    `WrappingCircuit` exposes the last elem_3 in row 0 of the instance column
+    and the two seeds in rows 1 and 2.
+
+*/
+
+use std::marker::PhantomData;
+
+use halo2_proofs::arithmetic::FieldExt;
+use halo2_proofs::circuit::{AssignedCell, Chip, Layouter, Region, SimpleFloorPlanner, Value};
+use halo2_proofs::plonk::*;
+use halo2_proofs::poly::Rotation;
+
+use crate::circuit::min_k;
+use crate::fibonacci::{ChainCells, FibonacciCells};
+use crate::range_check::{low_u64, RangeCheckChip, RangeCheckConfig, K};
+
+/// Computes the n-th term of the sequence seeded with `(elem_1, elem_2)` with
+/// wrapping u64 addition.
+pub fn nth_term(n: usize, elem_1: u64, elem_2: u64) -> u64 {
+    let (mut a, mut b) = (elem_1, elem_2);
+    for _ in 0..n {
+        (a, b) = (b, a.wrapping_add(b));
+    }
+    a
+}
+
+/// Columns and selector used by the `wrapping fibonacci` gate.
+#[derive(Clone, Debug, Copy)]
+pub struct WrappingConfig {
+    pub elem_1: Column<Advice>,
+    pub elem_2: Column<Advice>,
+    pub elem_3: Column<Advice>,
+    pub carry: Column<Advice>,
+    pub q_wrap: Selector,
+    pub instance: Column<Instance>,
+    pub range_check: RangeCheckConfig,
+}
+
+/// A chip proving one step `elem_3 = elem_1.wrapping_add(elem_2)` per row.
+#[derive(Clone, Debug)]
+pub struct WrappingChip<F: FieldExt> {
+    config: WrappingConfig,
+    _marker: PhantomData<F>,
+}
+
+impl<F: FieldExt> Chip<F> for WrappingChip<F> {
+    type Config = WrappingConfig;
+    type Loaded = ();
+
+    fn config(&self) -> &Self::Config {
+        &self.config
+    }
+
+    fn loaded(&self) -> &Self::Loaded {
+        &()
+    }
+}
+
+impl<F: FieldExt> WrappingChip<F> {
+    /// Constructs a chip from a config returned by [`WrappingChip::configure`].
+    pub fn construct(config: WrappingConfig) -> Self {
+        Self {
+            config,
+            _marker: PhantomData,
+        }
+    }
+
+    /// Allocates three equality-enabled advice columns, a carry column, a
+    /// selector and an instance column, and creates the `wrapping fibonacci`
+    /// gate over them. Also configures a [`RangeCheckChip`].
+    pub fn configure(cs: &mut ConstraintSystem<F>) -> WrappingConfig {
+        let elem_1 = cs.advice_column();
+        cs.enable_equality(elem_1);
+        let elem_2 = cs.advice_column();
+        cs.enable_equality(elem_2);
+        let elem_3 = cs.advice_column();
+        cs.enable_equality(elem_3);
+        let carry = cs.advice_column();
+        let q_wrap = cs.selector();
+        let instance = cs.instance_column();
+        cs.enable_equality(instance);
+        let range_check = RangeCheckChip::configure(cs);
+
+        cs.create_gate("wrapping fibonacci", |virtual_cells| {
+            let q_wrap = virtual_cells.query_selector(q_wrap);
+            let elem_1 = virtual_cells.query_advice(elem_1, Rotation::cur());
+            let elem_2 = virtual_cells.query_advice(elem_2, Rotation::cur());
+            let elem_3 = virtual_cells.query_advice(elem_3, Rotation::cur());
+            let carry = virtual_cells.query_advice(carry, Rotation::cur());
+
+            let one = Expression::Constant(F::one());
+            let two_pow_64 = Expression::Constant(F::from_u128(1 << 64));
+
+            Constraints::with_selector(
+                q_wrap,
+                [
+                    ("carry is boolean", carry.clone() * (one - carry.clone())),
+                    ("sum", elem_1 + elem_2 - elem_3 - carry * two_pow_64),
+                ],
+            )
+        });
+
+        WrappingConfig { elem_1, elem_2, elem_3, carry, q_wrap, instance, range_check }
+    }
+
+    /// Loads the range check table. Must be called once per circuit.
+    pub fn load_table(&self, layouter: impl Layouter<F>) -> Result<(), Error> {
+        RangeCheckChip::construct(self.config().range_check).load_table(layouter)
+    }
+
+    /// Assigns `elem_3 = elem_1.wrapping_add(elem_2)` and its carry in one row,
+    /// returning the cells holding `elem_1`, `elem_2` and `elem_3`.
+    fn assign_row(
+        &self,
+        mut layouter: impl Layouter<F>,
+        name: &str,
+        elem_1: impl Fn(&mut Region<'_, F>) -> Result<AssignedCell<F, F>, Error>,
+        elem_2: impl Fn(&mut Region<'_, F>) -> Result<AssignedCell<F, F>, Error>,
+    ) -> Result<(AssignedCell<F, F>, FibonacciCells<F>), Error> {
+        let config = self.config();
+
+        layouter.assign_region(|| name, |mut region| {
+            let offset = 0;
+
+            // Enable q_wrap
+            config.q_wrap.enable(&mut region, offset)?;
+
+            let elem_1 = elem_1(&mut region)?;
+            let elem_2 = elem_2(&mut region)?;
+
+            let sum = elem_1.value().zip(elem_2.value()).map(|(a, b)| low_u64(a) as u128 + low_u64(b) as u128);
+            // Assign elem_3 and carry
+            let elem_3 = region.assign_advice(|| "elem_3", config.elem_3, offset, || sum.map(|sum| F::from(sum as u64)))?;
+            region.assign_advice(|| "carry", config.carry, offset, || sum.map(|sum| F::from((sum >> 64) as u64)))?;
+
+            Ok((elem_1, (elem_2, elem_3)))
+        })
+    }
+
+    /// Assigns the first row of the chain from the two seeds `elem_1` and
+    /// `elem_2`, range-checking them and their wrapping sum `elem_3`.
+    pub fn init(
+        &self,
+        layouter: impl Layouter<F>,
+        elem_1: Value<u64>,
+        elem_2: Value<u64>,
+    ) -> Result<FibonacciCells<F>, Error> {
+        let (_, elem_2, elem_3) = self.init_with_seeds(layouter, elem_1, elem_2)?;
+        Ok((elem_2, elem_3))
+    }
+
+    /// Like [`WrappingChip::init`], but also returns the cell holding
+    /// `elem_1`, so that the seeds can be constrained by the caller.
+    pub fn init_with_seeds(
+        &self,
+        mut layouter: impl Layouter<F>,
+        elem_1: Value<u64>,
+        elem_2: Value<u64>,
+    ) -> Result<ChainCells<F>, Error> {
+        let config = self.config();
+        let range_check = RangeCheckChip::construct(config.range_check);
+
+        let (elem_1, (elem_2, elem_3)) = self.assign_row(
+            layouter.namespace(|| "init"),
+            "init wrapping Fibonacci",
+            |region| region.assign_advice(|| "elem_1", config.elem_1, 0, || elem_1.map(F::from)),
+            |region| region.assign_advice(|| "elem_2", config.elem_2, 0, || elem_2.map(F::from)),
+        )?;
+
+        for (name, cell) in [("elem_1", &elem_1), ("elem_2", &elem_2), ("elem_3", &elem_3)] {
+            range_check.copy_check(layouter.namespace(|| format!("range check {}", name)), cell, 64)?;
+        }
+        Ok((elem_1, elem_2, elem_3))
+    }
+
+    /// Assigns one more step of the chain, copying the previous `elem_2` and
+    /// `elem_3` into the new row and range-checking the new `elem_3`.
+    pub fn assign(
+        &self,
+        mut layouter: impl Layouter<F>,
+        prev: &FibonacciCells<F>,
+    ) -> Result<FibonacciCells<F>, Error> {
+        let config = self.config();
+        let range_check = RangeCheckChip::construct(config.range_check);
+        let (prev_2, prev_3) = prev;
+
+        let (_, (elem_2, elem_3)) = self.assign_row(
+            layouter.namespace(|| "step"),
+            "steady-state wrapping Fibonacci",
+            |region| prev_2.copy_advice(|| "copy elem_2 into current elem_1", region, config.elem_1, 0),
+            |region| prev_3.copy_advice(|| "copy elem_3 into current elem_2", region, config.elem_2, 0),
+        )?;
+
+        range_check.copy_check(layouter.namespace(|| "range check elem_3"), &elem_3, 64)?;
+        Ok((elem_2, elem_3))
+    }
+
+    /// Constrains `cell` to equal the given `row` of the instance column.
+    pub fn expose_public(
+        &self,
+        mut layouter: impl Layouter<F>,
+        cell: &AssignedCell<F, F>,
+        row: usize,
+    ) -> Result<(), Error> {
+        layouter.constrain_instance(cell.cell(), self.config().instance, row)
+    }
+}
+
+/// A circuit proving the n-th term of the wrapping u64 sequence seeded with
+/// `(elem_1, elem_2)`, exposing it in row 0 of the instance column and the
+/// seeds in rows 1 and 2.
+#[derive(Clone, Debug)]
+pub struct WrappingCircuit<F: FieldExt> {
+    n: usize,
+    elem_1: Value<u64>,
+    elem_2: Value<u64>,
+    _marker: PhantomData<F>,
+}
+
+impl<F: FieldExt> WrappingCircuit<F> {
+    /// Creates a circuit for the n-th term of the sequence seeded with
+    /// `(elem_1, elem_2)`, which must have `n >= 2`.
+    pub fn new(n: usize, elem_1: Value<u64>, elem_2: Value<u64>) -> Self {
+        assert!(n >= 2, "the circuit has at least one Fibonacci step");
+        Self {
+            n,
+            elem_1,
+            elem_2,
+            _marker: PhantomData,
+        }
+    }
+
+    /// The smallest `k` for which the circuit fits.
+    pub fn k(&self) -> u32 {
+        let mut cs = ConstraintSystem::default();
+        Self::configure(&mut cs);
+        // One row of the chain per step, and 64 / K + 1 rows per range check,
+        // with two more range checks for the seeds.
+        let rows = (self.n - 1) + (self.n + 1) * (64 / K + 1);
+        min_k(std::cmp::max(rows, 1 << K), &cs)
+    }
+}
+
+impl<F: FieldExt> Circuit<F> for WrappingCircuit<F> {
+    type Config = WrappingConfig;
+
+    type FloorPlanner = SimpleFloorPlanner;
+
+    fn without_witnesses(&self) -> Self {
+        Self::new(self.n, Value::unknown(), Value::unknown())
+    }
+
+    fn configure(meta: &mut ConstraintSystem<F>) -> Self::Config {
+        WrappingChip::configure(meta)
+    }
+
+    fn synthesize(&self, config: Self::Config, mut layouter: impl Layouter<F>) -> Result<(), Error> {
+        let chip = WrappingChip::construct(config);
+        chip.load_table(layouter.namespace(|| "table"))?;
+
+        let (seed_1, elem_2, elem_3) = chip.init_with_seeds(layouter.namespace(|| "init"), self.elem_1, self.elem_2)?;
+        let seed_2 = elem_2.clone();
+        let mut cells = (elem_2, elem_3);
+        for i in 3..=self.n {
+            cells = chip.assign(layouter.namespace(|| format!("x_{}", i)), &cells)?;
+        }
+
+        chip.expose_public(layouter.namespace(|| "out"), &cells.1, 0)?;
+        chip.expose_public(layouter.namespace(|| "elem_1"), &seed_1, 1)?;
+        chip.expose_public(layouter.namespace(|| "elem_2"), &seed_2, 2)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use halo2_proofs::{dev::MockProver, pasta::Fp};
+
+    use super::*;
+
+    fn public_inputs(n: usize, elem_1: u64, elem_2: u64) -> Vec<Fp> {
+        vec![Fp::from(nth_term(n, elem_1, elem_2)), Fp::from(elem_1), Fp::from(elem_2)]
+    }
+
+    #[test]
+    fn test_nth_term() {
+        assert_eq!(nth_term(93, 0, 1), 12200160415121876738);
+        assert_eq!(nth_term(94, 0, 1), 1293530146158671551);
+        assert_eq!(nth_term(10, u64::MAX, u64::MAX), 89u64.wrapping_mul(u64::MAX));
+    }
+
+    #[test]
+    fn test_wrapping() {
+        for (n, elem_1, elem_2) in [(10, 0, 1), (94, 0, 1), (150, 0, 1), (20, u64::MAX, u64::MAX), (3, 1 << 63, 1 << 63)] {
+            let circuit = WrappingCircuit::new(n, Value::known(elem_1), Value::known(elem_2));
+            let prover = MockProver::run(circuit.k(), &circuit, vec![public_inputs(n, elem_1, elem_2)]).unwrap();
+            prover.assert_satisfied();
+        }
+    }
+
+    #[test]
+    fn test_wrong_output() {
+        let circuit = WrappingCircuit::new(94, Value::known(0), Value::known(1));
+
+        // The field sum F(94) differs from its low 64 bits.
+        let output = crate::circuit::nth_term(94, Fp::zero(), Fp::one());
+        let prover = MockProver::run(circuit.k(), &circuit, vec![vec![output, Fp::zero(), Fp::one()]]).unwrap();
+        assert!(prover.verify().is_err());
+    }
+
+    #[test]
+    fn test_public_seeds() {
+        // x_10 = 34 * x_0 + 55 * x_1, so (355, 5) reaches 12345.
+        let circuit = WrappingCircuit::new(10, Value::known(355), Value::known(5));
+        let mut inputs = public_inputs(10, 355, 5);
+        assert_eq!(inputs[0], Fp::from(12345));
+
+        let prover = MockProver::run(circuit.k(), &circuit, vec![inputs.clone()]).unwrap();
+        prover.assert_satisfied();
+
+        // The output cannot be passed off as coming from the seeds 0 and 1.
+        inputs[1] = Fp::zero();
+        inputs[2] = Fp::one();
+        let prover = MockProver::run(circuit.k(), &circuit, vec![inputs]).unwrap();
+        assert!(prover.verify().is_err());
+    }
+}